@@ -2,14 +2,30 @@ extern crate rand;
 
 
 use rand::Rng;
-use std::collections::HashMap;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
 use std::fmt;
-use std::cmp::Ordering;
+use std::iter::Sum;
+use std::ops::Add;
+use crate::ability::random_scroll_ability;
+use crate::backpack::crafting::{format_materials, salvage_yield, Material};
+use crate::backpack::enchanting::{random_enchantment, Enchantment, UPGRADE_DAMAGE, UPGRADE_DEFENSE};
+use crate::backpack::gems::{generate_sockets, random_gem, Gem};
+use crate::backpack::requirements::{generate_requirements, Attribute, Requirements};
+use crate::backpack::sets::{find_set, random_set_piece};
+use crate::backpack::unique::random_unique;
+use crate::damage::{DamageType, Resistances};
+use crate::skill_tree::Class;
+use crate::status::{OnHitEffect, StatusEffect, StatusKind};
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash, Copy, PartialOrd)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Copy, PartialOrd, Ord, Serialize, Deserialize)]
 pub enum Slot {
     Weapon,
+    /// A second one-handed weapon. Only a position to equip into, items never roll it.
+    OffHand,
     Ring,
+    /// The other hand's ring. Only a position to equip into, items never roll it.
+    SecondRing,
     Helmet,
     Chestplate,
     Gauntlets,
@@ -24,17 +40,13 @@ pub enum Slot {
     Potion,
 }
 
-impl Ord for Slot {
-    fn cmp(&self, other: &Self) -> Ordering {
-        self.partial_cmp(other).unwrap()
-    }
-}
-
 impl fmt::Display for Slot {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let slot_str = match self {
             Slot::Weapon => "Weapon",
+            Slot::OffHand => "OffHand",
             Slot::Ring => "Ring",
+            Slot::SecondRing => "SecondRing",
             Slot::Boots => "Boots",
             Slot::Potion => "Potion",
             Slot::Helmet => "Helmet",
@@ -52,54 +64,466 @@ impl fmt::Display for Slot {
     }
 }
 
-#[derive(Debug, Clone)]
+/// Combat stats, rolled on items and carried as base values by entities.
+/// Everything but speed is in percentage points.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct Modifiers {
+    pub hit_chance: i128,
+    pub evasion: i128,
+    pub crit_chance: i128,
+    /// Extra damage dealt by a critical hit, e.g. 50 for +50%.
+    pub crit_multiplier: i128,
+    /// How far a damage roll can stray from the damage stat in either direction.
+    pub damage_variance: i128,
+    /// Decides turn order and the odds of extra turns.
+    #[serde(default)]
+    pub speed: i128,
+}
+
+impl Add for Modifiers {
+    type Output = Modifiers;
+
+    fn add(self, other: Modifiers) -> Modifiers {
+        Modifiers {
+            hit_chance: self.hit_chance + other.hit_chance,
+            evasion: self.evasion + other.evasion,
+            crit_chance: self.crit_chance + other.crit_chance,
+            crit_multiplier: self.crit_multiplier + other.crit_multiplier,
+            damage_variance: self.damage_variance + other.damage_variance,
+            speed: self.speed + other.speed,
+        }
+    }
+}
+
+impl Sum for Modifiers {
+    fn sum<I: Iterator<Item = Modifiers>>(iter: I) -> Modifiers {
+        iter.fold(Modifiers::default(), |total, modifiers| total + modifiers)
+    }
+}
+
+impl fmt::Display for Modifiers {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut parts: Vec<String> = [
+            (self.hit_chance, "Hit"),
+            (self.evasion, "Evasion"),
+            (self.crit_chance, "Crit"),
+            (self.crit_multiplier, "Crit Damage"),
+            (self.damage_variance, "Variance"),
+        ]
+        .iter()
+        .filter(|(value, _)| *value != 0)
+        .map(|(value, label)| format!("{:+}% {}", value, label))
+        .collect();
+        if self.speed != 0 {
+            parts.push(format!("{:+} Speed", self.speed));
+        }
+        write!(f, "{}", parts.join(", "))
+    }
+}
+
+/// Damage, combat stats and resistances granted together, like a set bonus
+/// or what a socketed gem adds.
+#[derive(Debug, Clone, Default)]
+pub struct StatBonus {
+    pub damage: i128,
+    pub modifiers: Modifiers,
+    pub resistances: Resistances,
+}
+
+impl fmt::Display for StatBonus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut parts = Vec::new();
+        if self.damage != 0 {
+            parts.push(format!("{:+} Damage", self.damage));
+        }
+        if self.modifiers != Modifiers::default() {
+            parts.push(self.modifiers.to_string());
+        }
+        if !self.resistances.is_empty() {
+            parts.push(self.resistances.to_string());
+        }
+        write!(f, "{}", parts.join(", "))
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct Item {
     pub name: String,
     pub description: String,
     pub damage_boost: i128,
     pub slot: Option<Slot>,
+    #[serde(default)]
+    pub modifiers: Modifiers,
+    /// Effect this item may inflict on whoever its wearer hits.
+    #[serde(default)]
+    pub on_hit: Option<OnHitEffect>,
+    /// Effect placed on the wearer at the start of every battle.
+    #[serde(default)]
+    pub wearer_effect: Option<StatusEffect>,
+    /// The kind of damage dealt when this item is the equipped weapon.
+    #[serde(default)]
+    pub damage_type: DamageType,
+    #[serde(default)]
+    pub resistances: Resistances,
+    /// Name of the ability a scroll teaches when read.
+    #[serde(default)]
+    pub teaches: Option<String>,
+    /// Flavour text shown under unique items.
+    #[serde(default)]
+    pub lore: Option<String>,
+    /// Only one unique item can be equipped at a time.
+    #[serde(default)]
+    pub unique: bool,
+    /// Name of the item set this piece belongs to.
+    #[serde(default)]
+    pub set: Option<String>,
+    /// Weapons that need both hands, leaving no room for a shield or off-hand weapon.
+    #[serde(default)]
+    pub two_handed: bool,
+    #[serde(default)]
+    pub requirements: Requirements,
+    /// Worn down by hits in battle. A broken item gives no stats until repaired.
+    #[serde(default)]
+    pub durability: i128,
+    /// Zero for items that never wear out, like potions.
+    #[serde(default)]
+    pub max_durability: i128,
+    /// Durability a repair kit restores, zero for anything else.
+    #[serde(default)]
+    pub repair_amount: i128,
+    /// Extra copies carried under the same name, for items that stack.
+    #[serde(default)]
+    pub stacked: i128,
+    /// Successful upgrades so far, from 0 up to +10.
+    #[serde(default)]
+    pub upgrade_level: i128,
+    /// An extra affix, rerolled rather than stacked when enchanted again.
+    #[serde(default)]
+    pub enchantment: Option<Enchantment>,
+    /// How many gems the item can hold.
+    #[serde(default)]
+    pub sockets: usize,
+    /// Gems set into the item's sockets, never more than `sockets`.
+    #[serde(default)]
+    pub socketed: Vec<Gem>,
+    /// Set when the item is itself a gem waiting to be socketed.
+    #[serde(default)]
+    pub gem: Option<Gem>,
+}
+
+/// How hard an item is to come by, which decides what salvaging it yields.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Rarity {
+    Common,
+    Uncommon,
+    Rare,
+    Set,
+    Unique,
 }
+
+impl fmt::Display for Rarity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let rarity_str = match self {
+            Rarity::Common => "Common",
+            Rarity::Uncommon => "Uncommon",
+            Rarity::Rare => "Rare",
+            Rarity::Set => "Set",
+            Rarity::Unique => "Unique",
+        };
+        write!(f, "{}", rarity_str)
+    }
+}
+
+/// Health restored per point of damage boost when a consumable is used.
+const HEAL_PER_BOOST: i128 = 5;
+/// Gold the blacksmith charges for each point of durability restored.
+const REPAIR_COST_PER_POINT: i128 = 2;
+/// Chance in percent that a generated item is one of these special drops
+/// rather than an ordinary random item.
+const DROP_TABLE: [(i128, fn() -> Item); 4] = [
+    (2, random_unique),
+    (5, random_set_piece),
+    (8, random_repair_kit),
+    (6, random_gem),
+];
+
 impl Item {
     pub fn new_random() -> Self {
-        let name = generate_random_item_name();
+        Self::from_name(generate_random_item_name())
+    }
+
+    /// A random item of a given kind, e.g. `Item::new_random_with_suffix("Potion")`.
+    pub fn new_random_with_suffix(suffix: &str) -> Self {
+        Self::from_name(format!("{} {}", generate_random_item_prefix(), suffix))
+    }
+
+    /// An item built by the name generator from a "Prefix Suffix" name.
+    pub fn from_name(name: String) -> Self {
         let (description, damage_boost) = generate_random_item_description();
-        let (_, suffix) = parse_item_name(&name);
+        let (prefix, suffix) = parse_item_name(&name);
         let slot = determine_item_slot(&suffix); // Assign slot based on suffix
+        let max_durability = generate_max_durability(slot);
+        let (on_hit, wearer_effect) = determine_item_effects(prefix);
         Item {
             name: name.clone(),
             description,
             damage_boost,
             slot,
+            modifiers: generate_random_modifiers(slot),
+            on_hit,
+            wearer_effect,
+            damage_type: determine_damage_type(prefix, slot),
+            resistances: generate_random_resistances(prefix, slot),
+            teaches: (suffix == "Scroll").then(|| random_scroll_ability().name),
+            lore: None,
+            unique: false,
+            set: None,
+            two_handed: is_two_handed(&suffix),
+            requirements: generate_requirements(&suffix, slot, damage_boost),
+            durability: max_durability,
+            max_durability,
+            repair_amount: 0,
+            stacked: 0,
+            upgrade_level: 0,
+            // Enchanted items come out of the generator with an affix already on them
+            enchantment: (prefix == "Enchanted" && slot.is_some_and(|slot| slot != Slot::Potion))
+                .then(|| random_enchantment(slot)),
+            sockets: generate_sockets(slot),
+            socketed: Vec::new(),
+            gem: None,
+        }
+    }
+
+    /// Potions can be used up in battle instead of equipped. Scrolls are read instead.
+    pub fn is_consumable(&self) -> bool {
+        self.slot == Some(Slot::Potion) && self.teaches.is_none()
+    }
+
+    pub fn heal_amount(&self) -> i128 {
+        self.damage_boost * HEAL_PER_BOOST
+    }
+
+    /// Uniques and set pieces by their nature, generated items by their damage boost.
+    pub fn rarity(&self) -> Rarity {
+        if self.unique {
+            Rarity::Unique
+        } else if self.set.is_some() {
+            Rarity::Set
+        } else if self.damage_boost >= 8 {
+            Rarity::Rare
+        } else if self.damage_boost >= 5 {
+            Rarity::Uncommon
+        } else {
+            Rarity::Common
         }
     }
+
+    /// Anything worn in a slot, as opposed to potions, scrolls and repair kits.
+    pub fn is_gear(&self) -> bool {
+        self.slot.is_some_and(|slot| slot != Slot::Potion)
+    }
+
+    /// Armour pieces, whose upgrades add defense on top of damage.
+    pub fn is_armour(&self) -> bool {
+        matches!(
+            self.slot,
+            Some(Slot::Helmet)
+                | Some(Slot::Chestplate)
+                | Some(Slot::Gauntlets)
+                | Some(Slot::Greaves)
+                | Some(Slot::Boots)
+                | Some(Slot::Shield)
+                | Some(Slot::Pauldrons)
+                | Some(Slot::Bracers)
+                | Some(Slot::Belt)
+        )
+    }
+
+    /// What each socketed gem adds, depending on the slot of this item.
+    pub fn gem_bonuses(&self) -> Vec<StatBonus> {
+        self.socketed.iter().map(|gem| gem.bonus(self.slot)).collect()
+    }
+
+    /// Damage boost plus whatever the socketed gems add.
+    pub fn total_damage(&self) -> i128 {
+        self.damage_boost + self.gem_bonuses().iter().map(|bonus| bonus.damage).sum::<i128>()
+    }
+
+    /// Rolled modifiers plus whatever the enchantment and gems add.
+    pub fn total_modifiers(&self) -> Modifiers {
+        let enchanted = match &self.enchantment {
+            Some(enchantment) => self.modifiers + enchantment.modifiers(),
+            None => self.modifiers,
+        };
+        self.gem_bonuses().iter().fold(enchanted, |total, bonus| total + bonus.modifiers)
+    }
+
+    /// Rolled resistances plus whatever the enchantment and gems add.
+    pub fn total_resistances(&self) -> Resistances {
+        let mut resistances = self.resistances.clone();
+        if let Some(enchantment) = &self.enchantment {
+            resistances.combine(&enchantment.resistances());
+        }
+        for bonus in self.gem_bonuses() {
+            resistances.combine(&bonus.resistances);
+        }
+        resistances
+    }
+
+    /// Raises the upgrade level by one, making the item stronger.
+    pub fn upgrade(&mut self) {
+        self.upgrade_level += 1;
+        self.damage_boost += UPGRADE_DAMAGE;
+        if self.is_armour() {
+            self.resistances.add(DamageType::Physical, UPGRADE_DEFENSE);
+        }
+    }
+
+    pub fn is_broken(&self) -> bool {
+        self.max_durability > 0 && self.durability <= 0
+    }
+
+    pub fn is_repair_kit(&self) -> bool {
+        self.repair_amount > 0
+    }
+
+    /// Items that are used up rather than worn, so copies with the same name
+    /// share one entry. A stack takes on the stats of its first copy.
+    pub fn is_stackable(&self) -> bool {
+        self.is_repair_kit() || self.gem.is_some() || self.is_consumable()
+    }
+
+    pub fn missing_durability(&self) -> i128 {
+        self.max_durability - self.durability
+    }
+
+    /// Gold the blacksmith asks to fully repair the item.
+    pub fn repair_cost(&self) -> i128 {
+        self.missing_durability() * REPAIR_COST_PER_POINT
+    }
+
+    /// Restores up to `amount` durability and returns how much was restored.
+    pub fn repair(&mut self, amount: i128) -> i128 {
+        let restored = amount.max(0).min(self.missing_durability());
+        self.durability += restored;
+        restored
+    }
+
+    /// Takes a point of durability and returns true if that broke the item.
+    fn wear(&mut self) -> bool {
+        if self.max_durability <= 0 || self.is_broken() {
+            return false;
+        }
+        self.durability -= 1;
+        self.is_broken()
+    }
+}
+
+/// A kit that repairs some durability of any item, from the drop table.
+pub fn random_repair_kit() -> Item {
+    let kits = [("Crude", 20), ("Sturdy", 40), ("Masterwork", 80)];
+    let (quality, repair_amount) = kits[rand::thread_rng().gen_range(0..kits.len())];
+    Item {
+        name: format!("{} Repair Kit", quality),
+        description: "Tools and materials for patching up gear".to_string(),
+        repair_amount,
+        ..Item::default()
+    }
+}
+
+/// Gear gets between 40 and 80 durability, consumables don't wear.
+fn generate_max_durability(slot: Option<Slot>) -> i128 {
+    match slot {
+        None | Some(Slot::Potion) => 0,
+        Some(_) => rand::thread_rng().gen_range(40..=80),
+    }
 }
 
 impl fmt::Display for Item {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(
-            f,
-            "{}: {} (+{} Damage), Slot: {:?}",
-            self.name, self.description, self.damage_boost, self.slot
-        )
+        if self.unique {
+            write!(f, "[Unique] ")?;
+        }
+        write!(f, "{}", self.name)?;
+        if self.stacked > 0 {
+            write!(f, " x{}", self.stacked + 1)?;
+        }
+        if self.upgrade_level > 0 {
+            write!(f, " +{}", self.upgrade_level)?;
+        }
+        write!(f, ": {} (+{} Damage", self.description, self.damage_boost)?;
+        if self.slot == Some(Slot::Weapon) && self.damage_type != DamageType::Physical {
+            write!(f, " {}", self.damage_type)?;
+        }
+        if !self.resistances.is_empty() {
+            write!(f, ", {}", self.resistances)?;
+        }
+        if self.modifiers != Modifiers::default() {
+            write!(f, ", {}", self.modifiers)?;
+        }
+        if let Some(enchantment) = &self.enchantment {
+            write!(f, ", Enchanted: {}", enchantment)?;
+        }
+        if let Some(on_hit) = &self.on_hit {
+            write!(f, ", {}", on_hit)?;
+        }
+        if let Some(ability) = &self.teaches {
+            write!(f, ", teaches {}", ability)?;
+        }
+        if let Some(effect) = &self.wearer_effect {
+            write!(f, ", {} on wearer", effect.kind)?;
+        }
+        if self.two_handed {
+            write!(f, ", Two-Handed")?;
+        }
+        if !self.requirements.is_empty() {
+            write!(f, ", requires {}", self.requirements)?;
+        }
+        if self.is_repair_kit() {
+            write!(f, ", repairs {} durability", self.repair_amount)?;
+        }
+        if self.sockets > 0 {
+            let mut sockets: Vec<String> = self
+                .socketed
+                .iter()
+                .map(|gem| format!("{}: {}", gem, gem.bonus(self.slot)))
+                .collect();
+            sockets.resize(self.sockets.max(sockets.len()), "empty".to_string());
+            write!(f, ", Sockets [{}]", sockets.join(", "))?;
+        }
+        if self.is_broken() {
+            write!(f, ", BROKEN")?;
+        } else if self.max_durability > 0 {
+            write!(f, ", Durability {}/{}", self.durability, self.max_durability)?;
+        }
+        write!(f, "), Slot: {:?}", self.slot)
     }
 }
 
+const ITEM_PREFIXES: [&str; 10] = [
+    "Ancient",
+    "Mystical",
+    "Legendary",
+    "Powerful",
+    "Cursed",
+    "Divine",
+    "Epic",
+    "Strange",
+    "Exquisite",
+    "Enchanted",
+];
+
+fn generate_random_item_prefix() -> &'static str {
+    let mut rng = rand::thread_rng();
+    ITEM_PREFIXES[rng.gen_range(0..ITEM_PREFIXES.len())]
+}
+
 fn generate_random_item_name() -> String {
-    let prefixes = [
-        "Ancient",
-        "Mystical",
-        "Legendary",
-        "Powerful",
-        "Cursed",
-        "Divine",
-        "Epic",
-        "Strange",
-        "Exquisite",
-        "Enchanted",
-    ];
     let suffixes = [
         "Sword",
         "Staff",
+        "Dagger",
+        "Greataxe",
         "Ring",
         "Amulet",
         "Potion",
@@ -118,10 +542,9 @@ fn generate_random_item_name() -> String {
     ];
 
     let mut rng = rand::thread_rng();
-    let prefix_index = rng.gen_range(0..prefixes.len());
     let suffix_index = rng.gen_range(0..suffixes.len());
 
-    format!("{} {}", prefixes[prefix_index], suffixes[suffix_index])
+    format!("{} {}", generate_random_item_prefix(), suffixes[suffix_index])
 }
 
 fn generate_random_item_description() -> (String, i128) {
@@ -146,6 +569,90 @@ fn generate_random_item_description() -> (String, i128) {
     (description.to_owned(), damage_boost)
 }
 
+/// Rolls the combat modifiers an item gets, depending on where it is worn.
+fn generate_random_modifiers(slot: Option<Slot>) -> Modifiers {
+    let mut rng = rand::thread_rng();
+    let mut modifiers = Modifiers::default();
+    match slot {
+        Some(Slot::Weapon) => {
+            modifiers.crit_multiplier = rng.gen_range(0..=50);
+            modifiers.damage_variance = rng.gen_range(0..=10);
+        }
+        Some(Slot::Gauntlets) | Some(Slot::Bracers) => modifiers.hit_chance = rng.gen_range(1..=8),
+        Some(Slot::Ring) | Some(Slot::Amulet) => modifiers.crit_chance = rng.gen_range(1..=6),
+        Some(Slot::Boots) => {
+            modifiers.evasion = rng.gen_range(1..=4);
+            modifiers.speed = rng.gen_range(1..=4);
+        }
+        Some(Slot::Cloak) => {
+            modifiers.evasion = rng.gen_range(1..=6);
+            modifiers.speed = rng.gen_range(0..=3);
+        }
+        Some(Slot::Greaves) => modifiers.speed = rng.gen_range(0..=2),
+        // Heavy armour slows the wearer down
+        Some(Slot::Chestplate) => modifiers.speed = -rng.gen_range(0..=2),
+        Some(Slot::Shield) | Some(Slot::Helmet) => modifiers.evasion = rng.gen_range(0..=3),
+        _ => {}
+    }
+    modifiers
+}
+
+/// Weapons deal the kind of damage their prefix suggests, anything else hits physically.
+fn determine_damage_type(prefix: &str, slot: Option<Slot>) -> DamageType {
+    if slot != Some(Slot::Weapon) {
+        return DamageType::Physical;
+    }
+    match prefix {
+        "Divine" => DamageType::Holy,
+        "Cursed" => DamageType::Shadow,
+        "Strange" => DamageType::Poison,
+        "Enchanted" | "Mystical" => DamageType::random_element(),
+        _ => DamageType::Physical,
+    }
+}
+
+/// Armour resists physical blows while jewellery and cloaks ward off an element.
+/// Divine and cursed pieces also shift holy and shadow resistance.
+fn generate_random_resistances(prefix: &str, slot: Option<Slot>) -> Resistances {
+    let mut rng = rand::thread_rng();
+    let mut resistances = Resistances::default();
+    match slot {
+        Some(Slot::Chestplate) | Some(Slot::Shield) => {
+            resistances.add(DamageType::Physical, rng.gen_range(5..=15));
+        }
+        Some(Slot::Helmet) | Some(Slot::Gauntlets) | Some(Slot::Greaves) | Some(Slot::Boots)
+        | Some(Slot::Pauldrons) | Some(Slot::Bracers) | Some(Slot::Belt) => {
+            resistances.add(DamageType::Physical, rng.gen_range(0..=8));
+        }
+        Some(Slot::Ring) | Some(Slot::Amulet) | Some(Slot::Cloak) => {
+            resistances.add(DamageType::random_element(), rng.gen_range(5..=20));
+        }
+        Some(Slot::Weapon) | Some(Slot::OffHand) | Some(Slot::SecondRing) | Some(Slot::Potion) | None => return resistances,
+    }
+    match prefix {
+        "Divine" => resistances.add(DamageType::Shadow, 20),
+        "Cursed" => {
+            resistances.add(DamageType::Shadow, 25);
+            resistances.add(DamageType::Holy, -25);
+        }
+        _ => {}
+    }
+    resistances
+}
+
+/// Cursed items make their wearer's hits burn but poison the wearer too,
+/// while divine ones keep them regenerating.
+fn determine_item_effects(prefix: &str) -> (Option<OnHitEffect>, Option<StatusEffect>) {
+    match prefix {
+        "Cursed" => (
+            Some(OnHitEffect::new(StatusKind::Burn, 3, 2, 25)),
+            Some(StatusEffect::new(StatusKind::Poison, 3, 1)),
+        ),
+        "Divine" => (None, Some(StatusEffect::new(StatusKind::Regen, 3, 2))),
+        _ => (None, None),
+    }
+}
+
 fn parse_item_name(name: &str) -> (&str, String) {
     let parts: Vec<&str> = name.split_whitespace().collect();
     if let [first, last] = parts.as_slice() {
@@ -155,9 +662,13 @@ fn parse_item_name(name: &str) -> (&str, String) {
     }
 }
 
+fn is_two_handed(suffix: &str) -> bool {
+    matches!(suffix, "Staff" | "Greataxe")
+}
+
 fn determine_item_slot(suffix: &str) -> Option<Slot> {
     match suffix {
-        "Sword" | "Staff" => Some(Slot::Weapon),
+        "Sword" | "Staff" | "Dagger" | "Greataxe" => Some(Slot::Weapon),
         "Helmet" => Some(Slot::Helmet),
         "Chestplate" => Some(Slot::Chestplate),
         "Gauntlets" => Some(Slot::Gauntlets),
@@ -176,10 +687,59 @@ fn determine_item_slot(suffix: &str) -> Option<Slot> {
 }
 
 
-    #[derive(Debug, Clone)]
+/// Why an item couldn't be equipped.
+#[derive(Debug, Clone, PartialEq)]
+pub enum EquipError {
+    NotFound,
+    NoSlot,
+    LevelTooLow { required: i128, level: i128 },
+    WrongClass { allowed: Vec<Class> },
+    AttributeTooLow { attribute: Attribute, required: i128, value: i128 },
+    /// Holds the name of the unique item already equipped.
+    UniqueEquipped(String),
+    TwoHandedEquipped { weapon: String, item: String },
+    OffHandBusy { off_hand: String, item: String },
+    NeedsBothHands { item: String, blocking: String },
+}
+
+impl fmt::Display for EquipError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EquipError::NotFound => write!(f, "Item not found in the backpack."),
+            EquipError::NoSlot => write!(f, "Item cannot be equipped because it doesn't have a valid slot."),
+            EquipError::LevelTooLow { required, level } => {
+                write!(f, "You need to be level {} to equip this, but you are level {}.", required, level)
+            }
+            EquipError::WrongClass { allowed } => {
+                let classes: Vec<String> = allowed.iter().map(|class| class.to_string()).collect();
+                write!(f, "Only a {} can equip this.", classes.join(" or "))
+            }
+            EquipError::AttributeTooLow { attribute, required, value } => {
+                write!(f, "You need {} {} to equip this, but you have {}.", required, attribute, value)
+            }
+            EquipError::UniqueEquipped(other) => {
+                write!(f, "Only one unique item can be equipped at a time and {} is already equipped.", other)
+            }
+            EquipError::TwoHandedEquipped { weapon, item } => {
+                write!(f, "{} is two-handed, so there is no hand free for {}.", weapon, item)
+            }
+            EquipError::OffHandBusy { off_hand, item } => {
+                write!(f, "Your off hand is holding {}. Unequip it before using {}.", off_hand, item)
+            }
+            EquipError::NeedsBothHands { item, blocking } => {
+                write!(f, "{} needs both hands. Unequip {} first.", item, blocking)
+            }
+        }
+    }
+}
+
+    #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Backpack {
     pub items: HashMap<String, Item>,
     pub equipped_items: HashMap<Slot, Item>,
+    /// Crafting materials and how many of each are carried.
+    #[serde(default)]
+    pub materials: BTreeMap<Material, i128>,
 }
 
 impl Backpack {
@@ -187,10 +747,101 @@ impl Backpack {
         Backpack {
             items: HashMap::new(),
             equipped_items: HashMap::new(),
+            materials: BTreeMap::new(),
         }
     }
     pub fn add_item(&mut self, item: Item) {
-        self.items.insert(item.name.clone(), item);
+        match self.items.get_mut(&item.name) {
+            Some(stack) if stack.is_stackable() && item.is_stackable() => stack.stacked += item.stacked + 1,
+            _ => {
+                self.items.insert(item.name.clone(), item);
+            }
+        }
+    }
+
+    /// Takes an item out of the backpack, just one copy of it if it is stacked.
+    pub fn take_item(&mut self, item_name: &str) -> Option<Item> {
+        match self.items.get_mut(item_name) {
+            Some(stack) if stack.stacked > 0 => {
+                stack.stacked -= 1;
+                Some(Item {
+                    stacked: 0,
+                    ..stack.clone()
+                })
+            }
+            _ => self.items.remove(item_name),
+        }
+    }
+
+    /// Unequipped items that can be used up, sorted by name.
+    pub fn consumables(&self) -> Vec<&Item> {
+        let mut consumables: Vec<&Item> = self.items.values().filter(|item| item.is_consumable()).collect();
+        consumables.sort_by(|a, b| a.name.cmp(&b.name));
+        consumables
+    }
+
+    pub fn add_material(&mut self, material: Material, amount: i128) {
+        if amount > 0 {
+            *self.materials.entry(material).or_insert(0) += amount;
+        }
+    }
+
+    /// Materials carried and their counts, in a fixed order.
+    pub fn material_list(&self) -> Vec<(Material, i128)> {
+        self.materials.iter().map(|(material, amount)| (*material, *amount)).collect()
+    }
+
+    pub fn material_count(&self, material: Material) -> i128 {
+        self.materials.get(&material).copied().unwrap_or(0)
+    }
+
+    /// Uses up the given materials, or none of them if any are short.
+    pub fn remove_materials(&mut self, materials: &[(Material, i128)]) -> bool {
+        if materials.iter().any(|(material, amount)| self.material_count(*material) < *amount) {
+            return false;
+        }
+        for (material, amount) in materials {
+            let count = self.materials.entry(*material).or_insert(0);
+            *count -= amount;
+            if *count <= 0 {
+                self.materials.remove(material);
+            }
+        }
+        true
+    }
+
+    pub fn take_materials(&mut self) -> BTreeMap<Material, i128> {
+        std::mem::take(&mut self.materials)
+    }
+
+    /// Destroys an unequipped item and adds the materials it breaks down into.
+    pub fn salvage_item(&mut self, item_name: &str) -> Result<Vec<(Material, i128)>, String> {
+        let item = self.take_item(item_name).ok_or("Item not found in the backpack.")?;
+        // Socketed gems are prised out first rather than destroyed
+        for gem in &item.socketed {
+            self.add_item(gem.to_item());
+        }
+        let materials = salvage_yield(&item);
+        for (material, amount) in &materials {
+            self.add_material(*material, *amount);
+        }
+        Ok(materials)
+    }
+
+    /// Salvages every unequipped piece of gear below the given rarity, returning
+    /// what each one broke down into.
+    pub fn salvage_below(&mut self, rarity: Rarity) -> Vec<(String, Vec<(Material, i128)>)> {
+        let mut names: Vec<String> = self
+            .items
+            .values()
+            .filter(|item| item.is_gear() && item.rarity() < rarity)
+            .map(|item| item.name.clone())
+            .collect();
+        names.sort();
+        names
+            .into_iter()
+            .filter_map(|name| self.salvage_item(&name).ok().map(|materials| (name, materials)))
+            .collect()
     }
 
     pub fn remove_items(&mut self) -> Vec<Option<Item>> {
@@ -204,7 +855,7 @@ impl Backpack {
     } else {
         // Collect inventory items and sort by slot, then by damage boost
         let mut inventory_items: Vec<&Item> = self.items.values().collect();
-        inventory_items.sort_by_key(|item| (item.slot.clone(), item.damage_boost));
+        inventory_items.sort_by_key(|item| (item.slot, item.damage_boost));
 
         // Print inventory items grouped by slot
         println!("{} items in the Inventory:", self.items.len());
@@ -216,67 +867,319 @@ impl Backpack {
     } else {
         // Collect equipped items and sort by slot, then by damage boost
         let mut equipped_items: Vec<&Item> = self.equipped_items.values().collect();
-        equipped_items.sort_by_key(|item| (item.slot.clone(), item.damage_boost));
+        equipped_items.sort_by_key(|item| (item.slot, item.damage_boost));
 
         // Print equipped items grouped by slot
         println!("{} items equipped:", self.equipped_items.len());
         self.pretty_print_items(&equipped_items);
+        self.print_set_bonuses();
+    }
+
+    if !self.materials.is_empty() {
+        println!("Materials: {}", format_materials(&self.material_list()));
     }
 
     println!("\n");
 }
 
 fn pretty_print_items(&self, items: &[&Item]) {
-    let mut current_slot: Option<&Slot> = None;
+    let mut current_slot: Option<Option<Slot>> = None;
 
     for item in items {
-        if current_slot.is_none() || current_slot != item.slot.as_ref() {
-            current_slot = item.slot.as_ref();
-            println!("Slot: {:?}", item.slot.unwrap());
+        if current_slot != Some(item.slot) {
+            current_slot = Some(item.slot);
+            match item.slot {
+                Some(slot) => println!("Slot: {:?}", slot),
+                // Repair kits and the like can't be worn anywhere
+                None => println!("Other:"),
+            }
         }
         println!("    {}", item);
+        if let Some(lore) = &item.lore {
+            println!("        \"{}\"", lore);
+        }
     }
 }
 
+    /// Equipped items that aren't broken, the only ones that count towards stats.
+    fn working_items(&self) -> impl Iterator<Item = &Item> {
+        self.equipped_items.values().filter(|item| !item.is_broken())
+    }
+
     pub fn calculate_total_damage(&self) -> i128 {
+        self.working_items()
+            .map(|item| item.total_damage())
+            .sum()
+    }
+
+    pub fn calculate_total_modifiers(&self) -> Modifiers {
+        self.working_items()
+            .map(|item| item.total_modifiers())
+            .sum()
+    }
+
+    /// How many pieces of each set are equipped.
+    pub fn equipped_set_counts(&self) -> BTreeMap<String, usize> {
+        let mut counts = BTreeMap::new();
+        for set in self.working_items().filter_map(|item| item.set.as_ref()) {
+            *counts.entry(set.clone()).or_insert(0) += 1;
+        }
+        counts
+    }
+
+    /// Every set bonus unlocked by the equipped pieces.
+    pub fn active_set_bonuses(&self) -> Vec<StatBonus> {
+        self.equipped_set_counts()
+            .iter()
+            .filter_map(|(name, count)| find_set(name).map(|set| (set, *count)))
+            .flat_map(|(set, count)| set.bonuses.into_iter().filter(move |bonus| bonus.pieces <= count))
+            .map(|set_bonus| set_bonus.bonus)
+            .collect()
+    }
+
+    pub fn print_set_bonuses(&self) {
+        for (name, count) in self.equipped_set_counts() {
+            let Some(set) = find_set(&name) else {
+                continue;
+            };
+            println!("{} set ({}/{} equipped):", set.name, count, set.pieces.len());
+            for bonus in &set.bonuses {
+                let status = if bonus.pieces <= count { "[active]" } else { "[inactive]" };
+                println!("    {} {}", bonus, status);
+            }
+        }
+    }
+
+    pub fn calculate_total_resistances(&self) -> Resistances {
+        let mut resistances = Resistances::default();
+        for item in self.working_items() {
+            resistances.combine(&item.total_resistances());
+        }
+        resistances
+    }
+
+    /// The damage type of the equipped weapon, if there is one.
+    pub fn weapon_damage_type(&self) -> Option<DamageType> {
         self.equipped_items
+            .get(&Slot::Weapon)
+            .filter(|item| !item.is_broken())
+            .map(|item| item.damage_type)
+    }
+
+    pub fn equipped_on_hit_effects(&self) -> Vec<OnHitEffect> {
+        self.working_items()
+            .filter_map(|item| item.on_hit)
+            .collect()
+    }
+
+    pub fn equipped_wearer_effects(&self) -> Vec<StatusEffect> {
+        self.working_items()
+            .filter_map(|item| item.wearer_effect)
+            .collect()
+    }
+
+    /// Wears down the equipped weapons after landing a hit, returning the names of any that broke.
+    pub fn wear_weapons(&mut self) -> Vec<String> {
+        self.equipped_items
+            .iter_mut()
+            .filter(|(slot, _)| matches!(slot, Slot::Weapon | Slot::OffHand))
+            .filter_map(|(_, item)| item.wear().then(|| item.name.clone()))
+            .collect()
+    }
+
+    /// Wears down one random piece of armour after taking a hit, returning its name if it broke.
+    pub fn wear_armour(&mut self) -> Option<String> {
+        let mut armour: Vec<&mut Item> = self
+            .equipped_items
+            .values_mut()
+            .filter(|item| item.is_armour() && item.max_durability > 0 && !item.is_broken())
+            .collect();
+        if armour.is_empty() {
+            return None;
+        }
+        let index = rand::thread_rng().gen_range(0..armour.len());
+        let item = &mut armour[index];
+        item.wear().then(|| item.name.clone())
+    }
+
+    /// Looks an item up by name, whether it is equipped or not.
+    pub fn find_item(&self, item_name: &str) -> Option<&Item> {
+        self.items
+            .get(item_name)
+            .or_else(|| self.equipped_items.values().find(|item| item.name == item_name))
+    }
+
+    /// Like `find_item`, for changing the item in place.
+    pub fn find_item_mut(&mut self, item_name: &str) -> Option<&mut Item> {
+        if self.items.contains_key(item_name) {
+            return self.items.get_mut(item_name);
+        }
+        self.equipped_items.values_mut().find(|item| item.name == item_name)
+    }
+
+    /// Every piece of gear, equipped or not, sorted by name.
+    pub fn gear(&self) -> Vec<&Item> {
+        let mut gear: Vec<&Item> = self
+            .equipped_items
             .values()
-            .map(|item| item.damage_boost)
-            .sum()
+            .chain(self.items.values())
+            .filter(|item| item.is_gear())
+            .collect();
+        gear.sort_by(|a, b| a.name.cmp(&b.name));
+        gear
+    }
+
+    /// Unsocketed gems, sorted by name.
+    pub fn gems(&self) -> Vec<&Item> {
+        let mut gems: Vec<&Item> = self.items.values().filter(|item| item.gem.is_some()).collect();
+        gems.sort_by(|a, b| a.name.cmp(&b.name));
+        gems
+    }
+
+    /// Sets a gem from the backpack into a free socket of an item, equipped or not.
+    pub fn socket_gem(&mut self, gem_name: &str, item_name: &str) -> Result<(), String> {
+        let gem = self
+            .items
+            .get(gem_name)
+            .and_then(|item| item.gem)
+            .ok_or("Gem not found in the backpack.")?;
+        let item = self.find_item_mut(item_name).ok_or("Item not found in the backpack.")?;
+        if item.socketed.len() >= item.sockets {
+            return Err(format!("{} has no free sockets.", item.name));
+        }
+        item.socketed.push(gem);
+        self.take_item(gem_name);
+        Ok(())
+    }
+
+    /// Takes the gem out of a socket and puts it back in the backpack, returning its name.
+    pub fn unsocket_gem(&mut self, item_name: &str, socket: usize) -> Result<String, String> {
+        let item = self.find_item_mut(item_name).ok_or("Item not found in the backpack.")?;
+        if socket >= item.socketed.len() {
+            return Err(format!("{} has no gem in socket {}.", item.name, socket));
+        }
+        let gem = item.socketed.remove(socket).to_item();
+        let name = gem.name.clone();
+        self.add_item(gem);
+        Ok(name)
+    }
+
+    /// Every item, equipped or not, that has lost some durability.
+    pub fn damaged_items(&self) -> Vec<&Item> {
+        let mut damaged: Vec<&Item> = self
+            .equipped_items
+            .values()
+            .chain(self.items.values())
+            .filter(|item| item.missing_durability() > 0)
+            .collect();
+        damaged.sort_by(|a, b| a.name.cmp(&b.name));
+        damaged
     }
 
-    pub fn equip_item(&mut self, item_name: &str) -> Result<(), String> {
+    pub fn repair_kits(&self) -> Vec<&Item> {
+        let mut kits: Vec<&Item> = self.items.values().filter(|item| item.is_repair_kit()).collect();
+        kits.sort_by(|a, b| a.name.cmp(&b.name));
+        kits
+    }
+
+    /// Equips an item from the backpack into the position its slot allows,
+    /// putting whatever was there back into the backpack. Entities that can
+    /// dual wield put a second one-handed weapon in their off hand.
+    pub fn equip_item(&mut self, item_name: &str, dual_wield: bool) -> Result<(), EquipError> {
         println!("Equipping item: {}", item_name);
-        if let Some(item) = self.items.remove(item_name) {
-            if let Some(slot) = item.slot {
-                // Check if there's already an item in the slot
-                if let Some(prev_item) = self.equipped_items.insert(slot.clone(), item.clone()) {
-                    // Put back the previously equipped item into the items map
-                    self.items.insert(prev_item.name.clone(), prev_item.clone());
-                }
-                // Equip the new item
-                self.equipped_items.insert(slot, item.clone());
-                println!("Equipped item: {}", item.name);
-                Ok(())
+        let item = self.items.get(item_name).ok_or(EquipError::NotFound)?;
+        let slot = self.equip_position(item, dual_wield)?;
+        if item.unique {
+            if let Some((_, other)) = self
+                .equipped_items
+                .iter()
+                .find(|(equipped_slot, equipped)| **equipped_slot != slot && equipped.unique)
+            {
+                return Err(EquipError::UniqueEquipped(other.name.clone()));
+            }
+        }
+
+        let Some(item) = self.take_item(item_name) else {
+            return Err(EquipError::NotFound);
+        };
+        println!("Equipped item: {} ({})", item.name, slot);
+        if let Some(prev_item) = self.equipped_items.insert(slot, item) {
+            // Put back the previously equipped item into the items map
+            self.add_item(prev_item);
+        }
+        Ok(())
+    }
+
+    /// Where an item would go if equipped, or why it can't be.
+    fn equip_position(&self, item: &Item, dual_wield: bool) -> Result<Slot, EquipError> {
+        let Some(slot) = item.slot else {
+            return Err(EquipError::NoSlot);
+        };
+        let equipped = |slot: Slot| self.equipped_items.get(&slot);
+
+        match slot {
+            Slot::Ring => Ok(if equipped(Slot::Ring).is_none() || equipped(Slot::SecondRing).is_some() {
+                Slot::Ring
             } else {
-                self.items.insert(item.name.clone(), item.clone());
-                Err("Item cannot be equipped because it doesn't have a valid slot.".to_string())
+                Slot::SecondRing
+            }),
+            Slot::Shield => {
+                if let Some(weapon) = equipped(Slot::Weapon).filter(|weapon| weapon.two_handed) {
+                    Err(EquipError::TwoHandedEquipped {
+                        weapon: weapon.name.clone(),
+                        item: item.name.clone(),
+                    })
+                } else if let Some(off_hand) = equipped(Slot::OffHand) {
+                    Err(EquipError::OffHandBusy {
+                        off_hand: off_hand.name.clone(),
+                        item: item.name.clone(),
+                    })
+                } else {
+                    Ok(Slot::Shield)
+                }
             }
-        } else {
-            Err("Item not found in the backpack.".to_string())
+            Slot::Weapon if item.two_handed => {
+                match equipped(Slot::Shield).or(equipped(Slot::OffHand)) {
+                    Some(blocking) => Err(EquipError::NeedsBothHands {
+                        item: item.name.clone(),
+                        blocking: blocking.name.clone(),
+                    }),
+                    None => Ok(Slot::Weapon),
+                }
+            }
+            Slot::Weapon => match equipped(Slot::Weapon) {
+                Some(main_hand)
+                    if dual_wield
+                        && !main_hand.two_handed
+                        && equipped(Slot::OffHand).is_none()
+                        && equipped(Slot::Shield).is_none() =>
+                {
+                    Ok(Slot::OffHand)
+                }
+                _ => Ok(Slot::Weapon),
+            },
+            slot => Ok(slot),
         }
     }
 
     pub fn unequip_item(&mut self, slot: &Slot) -> Option<Item> {
         if let Some(item) = self.equipped_items.remove(slot) {
-            self.items.insert(item.name.clone(), item.clone());
+            self.add_item(item.clone());
             Some(item)
         } else {
             None
         }
     }
 
+    /// A random item, or on a rare roll one of the drops from `DROP_TABLE`.
     pub fn generate_item(&self) -> Item {
+        let mut roll = rand::thread_rng().gen_range(0..100);
+        for (chance, drop) in DROP_TABLE {
+            if roll < chance {
+                return drop();
+            }
+            roll -= chance;
+        }
         Item::new_random()
     }
 }
@@ -0,0 +1,182 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use crate::backpack::backpack::{Backpack, Item, Rarity, Slot};
+use crate::damage::DamageType;
+
+/// Dropped by enemies and used up by recipes. Kept as counts in the backpack
+/// rather than as items, so they stack.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum Material {
+    ScrapMetal,
+    Leather,
+    Bone,
+    ArcaneDust,
+    EmberEssence,
+    Herbs,
+}
+
+impl fmt::Display for Material {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let material_str = match self {
+            Material::ScrapMetal => "Scrap Metal",
+            Material::Leather => "Leather",
+            Material::Bone => "Bone",
+            Material::ArcaneDust => "Arcane Dust",
+            Material::EmberEssence => "Ember Essence",
+            Material::Herbs => "Herbs",
+        };
+        write!(f, "{}", material_str)
+    }
+}
+
+/// Formats a list of materials like "3 Bone, 2 Leather".
+pub fn format_materials(materials: &[(Material, i128)]) -> String {
+    let parts: Vec<String> = materials.iter().map(|(material, amount)| format!("{} {}", amount, material)).collect();
+    parts.join(", ")
+}
+
+#[derive(Debug, Clone)]
+pub enum RecipeOutput {
+    /// Run through the item generator by name, so the prefix decides effects
+    /// and damage type just like a dropped item.
+    Gear(&'static str),
+    /// A repair kit restoring this much durability.
+    RepairKit(i128),
+}
+
+#[derive(Debug, Clone)]
+pub struct Recipe {
+    pub name: &'static str,
+    /// Learned on reaching this level.
+    pub level: i128,
+    pub materials: &'static [(Material, i128)],
+    pub gold: i128,
+    pub output: RecipeOutput,
+}
+
+impl Recipe {
+    /// Materials still needed on top of what the backpack holds.
+    pub fn missing(&self, backpack: &Backpack) -> Vec<(Material, i128)> {
+        self.materials
+            .iter()
+            .map(|(material, amount)| (*material, amount - backpack.material_count(*material)))
+            .filter(|(_, amount)| *amount > 0)
+            .collect()
+    }
+
+    pub fn craft_item(&self) -> Item {
+        match self.output {
+            RecipeOutput::Gear(name) => Item::from_name(name.to_string()),
+            RecipeOutput::RepairKit(repair_amount) => Item {
+                name: self.name.to_string(),
+                description: "Hammered together from scraps at a workbench".to_string(),
+                repair_amount,
+                ..Item::default()
+            },
+        }
+    }
+}
+
+impl fmt::Display for Recipe {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} - {}", self.name, format_materials(self.materials))?;
+        if self.gold > 0 {
+            write!(f, ", {} gold", self.gold)?;
+        }
+        Ok(())
+    }
+}
+
+/// Every recipe there is. Adding one here is all it takes to make it
+/// craftable once the player reaches its level.
+pub const RECIPES: &[Recipe] = &[
+    Recipe {
+        name: "Healing Draught",
+        level: 1,
+        materials: &[(Material::Herbs, 3)],
+        gold: 0,
+        output: RecipeOutput::Gear("Divine Potion"),
+    },
+    Recipe {
+        name: "Patchwork Repair Kit",
+        level: 1,
+        materials: &[(Material::ScrapMetal, 2), (Material::Leather, 1)],
+        gold: 0,
+        output: RecipeOutput::RepairKit(30),
+    },
+    Recipe {
+        name: "Forged Sword",
+        level: 1,
+        materials: &[(Material::ScrapMetal, 5), (Material::Leather, 1)],
+        gold: 10,
+        output: RecipeOutput::Gear("Powerful Sword"),
+    },
+    Recipe {
+        name: "Stitched Boots",
+        level: 2,
+        materials: &[(Material::Leather, 4)],
+        gold: 10,
+        output: RecipeOutput::Gear("Exquisite Boots"),
+    },
+    Recipe {
+        name: "Bone Helm",
+        level: 3,
+        materials: &[(Material::Bone, 6), (Material::Leather, 2)],
+        gold: 20,
+        output: RecipeOutput::Gear("Ancient Helmet"),
+    },
+    Recipe {
+        name: "Ember Staff",
+        level: 4,
+        materials: &[(Material::EmberEssence, 4), (Material::ArcaneDust, 3), (Material::Bone, 2)],
+        gold: 40,
+        output: RecipeOutput::Gear("Mystical Staff"),
+    },
+    Recipe {
+        name: "Warden's Chestplate",
+        level: 5,
+        materials: &[(Material::ScrapMetal, 10), (Material::Leather, 4), (Material::ArcaneDust, 2)],
+        gold: 60,
+        output: RecipeOutput::Gear("Legendary Chestplate"),
+    },
+];
+
+/// Recipes an entity of the given level has learned.
+pub fn known_recipes(level: i128) -> Vec<Recipe> {
+    RECIPES.iter().filter(|recipe| recipe.level <= level).cloned().collect()
+}
+
+/// What an item breaks down into when salvaged. The slot decides the
+/// materials and the rarity how many of them.
+pub fn salvage_yield(item: &Item) -> Vec<(Material, i128)> {
+    let amount = match item.rarity() {
+        Rarity::Common => 1,
+        Rarity::Uncommon => 2,
+        Rarity::Rare => 3,
+        Rarity::Set => 4,
+        Rarity::Unique => 6,
+    };
+    let mut materials = match item.slot {
+        Some(Slot::Weapon) | Some(Slot::OffHand) | Some(Slot::Shield) => vec![(Material::ScrapMetal, amount + 1)],
+        Some(Slot::Helmet) | Some(Slot::Chestplate) | Some(Slot::Gauntlets) | Some(Slot::Greaves) | Some(Slot::Pauldrons) => {
+            vec![(Material::ScrapMetal, amount), (Material::Leather, 1)]
+        }
+        Some(Slot::Boots) | Some(Slot::Bracers) | Some(Slot::Cloak) | Some(Slot::Belt) => vec![(Material::Leather, amount + 1)],
+        Some(Slot::Ring) | Some(Slot::SecondRing) | Some(Slot::Amulet) => vec![(Material::ArcaneDust, amount)],
+        Some(Slot::Potion) if item.teaches.is_some() => vec![(Material::ArcaneDust, 1)],
+        Some(Slot::Potion) => vec![(Material::Herbs, 1)],
+        None => match item.gem {
+            Some(gem) => vec![(Material::ArcaneDust, gem.tier)],
+            // Repair kits are mostly scrap to begin with
+            None => vec![(Material::ScrapMetal, 1)],
+        },
+    };
+    // Elemental gear leaves some of its power behind
+    match item.damage_type {
+        DamageType::Fire => materials.push((Material::EmberEssence, 1)),
+        DamageType::Physical => {}
+        _ if item.is_gear() => materials.push((Material::ArcaneDust, 1)),
+        _ => {}
+    }
+    materials
+}
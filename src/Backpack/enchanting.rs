@@ -0,0 +1,112 @@
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use crate::backpack::backpack::{Item, Modifiers, Slot};
+use crate::backpack::crafting::Material;
+use crate::damage::{DamageType, Resistances};
+
+pub const MAX_UPGRADE_LEVEL: i128 = 10;
+/// Chance in percent of an upgrade failing, per level the item already has.
+const UPGRADE_FAIL_CHANCE_PER_LEVEL: i128 = 9;
+const UPGRADE_GOLD_PER_LEVEL: i128 = 15;
+/// Damage boost every successful upgrade adds.
+pub const UPGRADE_DAMAGE: i128 = 1;
+/// Physical resistance every successful upgrade adds to armour, its defense.
+pub const UPGRADE_DEFENSE: i128 = 2;
+pub const ENCHANT_MATERIALS: [(Material, i128); 1] = [(Material::ArcaneDust, 2)];
+pub const ENCHANT_GOLD: i128 = 25;
+
+/// The single stat an enchantment adds to an item.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum Affix {
+    HitChance,
+    Evasion,
+    CritChance,
+    CritMultiplier,
+    Speed,
+    Resistance(DamageType),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Enchantment {
+    pub affix: Affix,
+    pub value: i128,
+}
+
+impl Enchantment {
+    pub fn modifiers(&self) -> Modifiers {
+        match self.affix {
+            Affix::HitChance => Modifiers { hit_chance: self.value, ..Modifiers::default() },
+            Affix::Evasion => Modifiers { evasion: self.value, ..Modifiers::default() },
+            Affix::CritChance => Modifiers { crit_chance: self.value, ..Modifiers::default() },
+            Affix::CritMultiplier => Modifiers { crit_multiplier: self.value, ..Modifiers::default() },
+            Affix::Speed => Modifiers { speed: self.value, ..Modifiers::default() },
+            Affix::Resistance(_) => Modifiers::default(),
+        }
+    }
+
+    pub fn resistances(&self) -> Resistances {
+        match self.affix {
+            Affix::Resistance(damage_type) => Resistances::new(&[(damage_type, self.value)]),
+            _ => Resistances::default(),
+        }
+    }
+}
+
+impl fmt::Display for Enchantment {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.affix {
+            Affix::Resistance(_) => write!(f, "{}", self.resistances()),
+            _ => write!(f, "{}", self.modifiers()),
+        }
+    }
+}
+
+/// A random enchantment suited to where the item is worn.
+pub fn random_enchantment(slot: Option<Slot>) -> Enchantment {
+    let mut rng = rand::thread_rng();
+    let affixes: &[Affix] = match slot {
+        Some(Slot::Weapon) | Some(Slot::OffHand) => &[Affix::HitChance, Affix::CritChance, Affix::CritMultiplier],
+        Some(Slot::Ring) | Some(Slot::SecondRing) | Some(Slot::Amulet) => &[Affix::CritChance, Affix::HitChance, Affix::Resistance(DamageType::random_element())],
+        Some(Slot::Boots) | Some(Slot::Cloak) => &[Affix::Evasion, Affix::Speed],
+        _ => &[Affix::Evasion, Affix::HitChance, Affix::Resistance(DamageType::random_element())],
+    };
+    let affix = affixes[rng.gen_range(0..affixes.len())];
+    let value = match affix {
+        Affix::HitChance | Affix::Evasion | Affix::CritChance => rng.gen_range(3..=10),
+        Affix::CritMultiplier => rng.gen_range(15..=40),
+        Affix::Speed => rng.gen_range(2..=5),
+        Affix::Resistance(_) => rng.gen_range(10..=25),
+    };
+    Enchantment { affix, value }
+}
+
+/// Chance in percent that upgrading the item to its next level fails.
+pub fn upgrade_fail_chance(item: &Item) -> i128 {
+    item.upgrade_level * UPGRADE_FAIL_CHANCE_PER_LEVEL
+}
+
+/// Materials and gold taken by an upgrade attempt, whether it works or not.
+/// Higher levels need more and, past +5, arcane dust on top.
+pub fn upgrade_cost(item: &Item) -> (Vec<(Material, i128)>, i128) {
+    let next_level = item.upgrade_level + 1;
+    let material = match item.slot {
+        Some(Slot::Boots) | Some(Slot::Bracers) | Some(Slot::Cloak) | Some(Slot::Belt) => Material::Leather,
+        Some(Slot::Ring) | Some(Slot::SecondRing) | Some(Slot::Amulet) => Material::ArcaneDust,
+        _ => Material::ScrapMetal,
+    };
+    let mut materials = vec![(material, next_level)];
+    if next_level > 5 {
+        if material == Material::ArcaneDust {
+            materials[0].1 += next_level - 5;
+        } else {
+            materials.push((Material::ArcaneDust, next_level - 5));
+        }
+    }
+    (materials, next_level * UPGRADE_GOLD_PER_LEVEL)
+}
+
+/// Whether an upgrade attempt succeeds.
+pub fn roll_upgrade(item: &Item) -> bool {
+    rand::thread_rng().gen_range(0..100) >= upgrade_fail_chance(item)
+}
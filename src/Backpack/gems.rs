@@ -0,0 +1,152 @@
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use crate::backpack::backpack::{Item, Modifiers, Slot, StatBonus};
+use crate::damage::{DamageType, Resistances};
+
+pub const MAX_SOCKETS: usize = 3;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum GemKind {
+    Ruby,
+    Sapphire,
+    Topaz,
+    Emerald,
+    Amethyst,
+    Diamond,
+}
+
+impl GemKind {
+    const ALL: [GemKind; 6] = [
+        GemKind::Ruby,
+        GemKind::Sapphire,
+        GemKind::Topaz,
+        GemKind::Emerald,
+        GemKind::Amethyst,
+        GemKind::Diamond,
+    ];
+
+    /// The element a gem guards against when set into armour.
+    fn resistance(&self) -> DamageType {
+        match self {
+            GemKind::Ruby => DamageType::Fire,
+            GemKind::Sapphire => DamageType::Ice,
+            GemKind::Topaz => DamageType::Lightning,
+            GemKind::Emerald => DamageType::Poison,
+            GemKind::Amethyst => DamageType::Shadow,
+            GemKind::Diamond => DamageType::Physical,
+        }
+    }
+}
+
+impl fmt::Display for GemKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let kind_str = match self {
+            GemKind::Ruby => "Ruby",
+            GemKind::Sapphire => "Sapphire",
+            GemKind::Topaz => "Topaz",
+            GemKind::Emerald => "Emerald",
+            GemKind::Amethyst => "Amethyst",
+            GemKind::Diamond => "Diamond",
+        };
+        write!(f, "{}", kind_str)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Gem {
+    pub kind: GemKind,
+    /// 1 for chipped up to 3 for perfect, scaling every bonus.
+    pub tier: i128,
+}
+
+impl Gem {
+    /// Weapons get offense, jewellery combat stats and anything else protection.
+    pub fn bonus(&self, slot: Option<Slot>) -> StatBonus {
+        let tier = self.tier;
+        match slot {
+            Some(Slot::Weapon) | Some(Slot::OffHand) => match self.kind {
+                GemKind::Ruby => StatBonus { damage: 2 * tier, ..StatBonus::default() },
+                GemKind::Sapphire => StatBonus {
+                    modifiers: Modifiers { crit_chance: 3 * tier, ..Modifiers::default() },
+                    ..StatBonus::default()
+                },
+                GemKind::Topaz => StatBonus {
+                    modifiers: Modifiers { hit_chance: 3 * tier, ..Modifiers::default() },
+                    ..StatBonus::default()
+                },
+                GemKind::Emerald => StatBonus {
+                    modifiers: Modifiers { crit_multiplier: 10 * tier, ..Modifiers::default() },
+                    ..StatBonus::default()
+                },
+                GemKind::Amethyst => StatBonus {
+                    damage: tier,
+                    modifiers: Modifiers { crit_chance: 2 * tier, ..Modifiers::default() },
+                    ..StatBonus::default()
+                },
+                GemKind::Diamond => StatBonus { damage: 3 * tier, ..StatBonus::default() },
+            },
+            Some(Slot::Ring) | Some(Slot::SecondRing) | Some(Slot::Amulet) => {
+                let modifiers = match self.kind {
+                    GemKind::Ruby => Modifiers { crit_multiplier: 10 * tier, ..Modifiers::default() },
+                    GemKind::Sapphire => Modifiers { evasion: 3 * tier, ..Modifiers::default() },
+                    GemKind::Topaz => Modifiers { speed: tier, ..Modifiers::default() },
+                    GemKind::Emerald => Modifiers { hit_chance: 3 * tier, ..Modifiers::default() },
+                    GemKind::Amethyst => Modifiers { crit_chance: 3 * tier, ..Modifiers::default() },
+                    GemKind::Diamond => Modifiers { evasion: 2 * tier, hit_chance: 2 * tier, ..Modifiers::default() },
+                };
+                StatBonus { modifiers, ..StatBonus::default() }
+            }
+            Some(_) => StatBonus {
+                resistances: Resistances::new(&[(self.kind.resistance(), 5 * tier)]),
+                ..StatBonus::default()
+            },
+            None => StatBonus::default(),
+        }
+    }
+
+    pub fn to_item(self) -> Item {
+        Item {
+            name: self.to_string(),
+            description: "A gem that can be set into a socket".to_string(),
+            gem: Some(self),
+            ..Item::default()
+        }
+    }
+}
+
+impl fmt::Display for Gem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let quality = match self.tier {
+            1 => "Chipped",
+            2 => "Flawed",
+            _ => "Perfect",
+        };
+        write!(f, "{} {}", quality, self.kind)
+    }
+}
+
+/// A random gem, perfect ones being the rarest.
+pub fn random_gem() -> Item {
+    let mut rng = rand::thread_rng();
+    let kind = GemKind::ALL[rng.gen_range(0..GemKind::ALL.len())];
+    let tier = match rng.gen_range(0..100) {
+        0..=59 => 1,
+        60..=89 => 2,
+        _ => 3,
+    };
+    Gem { kind, tier }.to_item()
+}
+
+/// How many sockets a generated item rolls, most having none.
+pub fn generate_sockets(slot: Option<Slot>) -> usize {
+    match slot {
+        None | Some(Slot::Potion) => 0,
+        Some(_) => match rand::thread_rng().gen_range(0..100) {
+            0..=49 => 0,
+            50..=79 => 1,
+            80..=94 => 2,
+            _ => MAX_SOCKETS,
+        },
+    }
+}
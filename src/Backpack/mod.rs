@@ -1 +1,8 @@
-pub mod backpack;
\ No newline at end of file
+#[allow(clippy::module_inception)]
+pub mod backpack;
+pub mod crafting;
+pub mod enchanting;
+pub mod gems;
+pub mod requirements;
+pub mod sets;
+pub mod unique;
@@ -0,0 +1,126 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use crate::backpack::backpack::{EquipError, Slot};
+use crate::entity::Entity;
+use crate::skill_tree::Class;
+
+/// A stat an item can demand a minimum of. Base values are checked so gear
+/// can't lift its wearer over its own requirement.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum Attribute {
+    Damage,
+    Health,
+    Mana,
+    Speed,
+}
+
+impl Attribute {
+    pub fn value(&self, entity: &Entity) -> i128 {
+        match self {
+            Attribute::Damage => entity.base_damage,
+            Attribute::Health => entity.max_health,
+            Attribute::Mana => entity.max_mana,
+            Attribute::Speed => entity.base_modifiers.speed,
+        }
+    }
+}
+
+impl fmt::Display for Attribute {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let attribute_str = match self {
+            Attribute::Damage => "Damage",
+            Attribute::Health => "Health",
+            Attribute::Mana => "Mana",
+            Attribute::Speed => "Speed",
+        };
+        write!(f, "{}", attribute_str)
+    }
+}
+
+/// What an entity needs before it can equip an item. Empty means anyone can.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Requirements {
+    pub level: i128,
+    /// Classes allowed to equip the item, any class if empty.
+    pub classes: Vec<Class>,
+    pub attributes: Vec<(Attribute, i128)>,
+}
+
+impl Requirements {
+    pub fn is_empty(&self) -> bool {
+        self.level <= 1 && self.classes.is_empty() && self.attributes.is_empty()
+    }
+
+    /// The first requirement the entity falls short of.
+    pub fn check(&self, entity: &Entity) -> Result<(), EquipError> {
+        if entity.level < self.level {
+            return Err(EquipError::LevelTooLow {
+                required: self.level,
+                level: entity.level,
+            });
+        }
+        if !self.classes.is_empty() && !entity.class.is_some_and(|class| self.classes.contains(&class)) {
+            return Err(EquipError::WrongClass {
+                allowed: self.classes.clone(),
+            });
+        }
+        for (attribute, required) in &self.attributes {
+            let value = attribute.value(entity);
+            if value < *required {
+                return Err(EquipError::AttributeTooLow {
+                    attribute: *attribute,
+                    required: *required,
+                    value,
+                });
+            }
+        }
+        Ok(())
+    }
+
+    pub fn is_met(&self, entity: &Entity) -> bool {
+        self.check(entity).is_ok()
+    }
+}
+
+impl fmt::Display for Requirements {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut parts = Vec::new();
+        if self.level > 1 {
+            parts.push(format!("Level {}", self.level));
+        }
+        if !self.classes.is_empty() {
+            let classes: Vec<String> = self.classes.iter().map(|class| class.to_string()).collect();
+            parts.push(classes.join(" or "));
+        }
+        for (attribute, required) in &self.attributes {
+            parts.push(format!("{} {}", required, attribute));
+        }
+        write!(f, "{}", parts.join(", "))
+    }
+}
+
+/// Requirements for a generated item, tougher the stronger the item is.
+pub fn generate_requirements(suffix: &str, slot: Option<Slot>, damage_boost: i128) -> Requirements {
+    if slot.is_none() || slot == Some(Slot::Potion) {
+        return Requirements::default();
+    }
+
+    let classes = match suffix {
+        "Staff" => vec![Class::Mage],
+        "Greataxe" => vec![Class::Warrior],
+        "Dagger" => vec![Class::Rogue, Class::Mage],
+        _ => Vec::new(),
+    };
+    let attributes = match suffix {
+        "Chestplate" | "Shield" => vec![(Attribute::Health, damage_boost * 8)],
+        "Greataxe" => vec![(Attribute::Damage, damage_boost / 2)],
+        "Staff" => vec![(Attribute::Mana, 20 + damage_boost)],
+        "Boots" | "Cloak" => vec![(Attribute::Speed, damage_boost / 2)],
+        _ => Vec::new(),
+    };
+    Requirements {
+        level: (damage_boost / 3).max(1),
+        classes,
+        attributes,
+    }
+}
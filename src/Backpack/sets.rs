@@ -0,0 +1,173 @@
+use rand::Rng;
+use std::fmt;
+use crate::backpack::backpack::{Item, Modifiers, Slot, StatBonus};
+use crate::backpack::requirements::Requirements;
+use crate::damage::{DamageType, Resistances};
+
+/// Level needed to equip any set piece.
+const SET_PIECE_LEVEL: i128 = 3;
+const SET_PIECE_DURABILITY: i128 = 100;
+
+/// Granted while at least `pieces` items of the set are equipped. Bonuses
+/// for fewer pieces stay active, so they add up.
+#[derive(Debug, Clone)]
+pub struct SetBonus {
+    pub pieces: usize,
+    pub bonus: StatBonus,
+}
+
+impl fmt::Display for SetBonus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} pieces: {}", self.pieces, self.bonus)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ItemSet {
+    pub name: &'static str,
+    /// Slot and name of every piece.
+    pub pieces: Vec<(Slot, &'static str)>,
+    pub bonuses: Vec<SetBonus>,
+}
+
+pub fn item_sets() -> Vec<ItemSet> {
+    vec![
+        ItemSet {
+            name: "Vanguard",
+            pieces: vec![
+                (Slot::Helmet, "Vanguard Helm"),
+                (Slot::Chestplate, "Vanguard Breastplate"),
+                (Slot::Gauntlets, "Vanguard Gauntlets"),
+                (Slot::Greaves, "Vanguard Greaves"),
+                (Slot::Boots, "Vanguard Sabatons"),
+                (Slot::Shield, "Vanguard Tower Shield"),
+            ],
+            bonuses: vec![
+                SetBonus {
+                    pieces: 2,
+                    bonus: StatBonus {
+                        damage: 0,
+                        modifiers: Modifiers::default(),
+                        resistances: Resistances::new(&[(DamageType::Physical, 10)]),
+                    },
+                },
+                SetBonus {
+                    pieces: 4,
+                    bonus: StatBonus {
+                        damage: 4,
+                        modifiers: Modifiers { hit_chance: 5, ..Modifiers::default() },
+                        resistances: Resistances::default(),
+                    },
+                },
+                SetBonus {
+                    pieces: 6,
+                    bonus: StatBonus {
+                        damage: 8,
+                        modifiers: Modifiers { crit_chance: 10, ..Modifiers::default() },
+                        resistances: Resistances::new(&[(DamageType::Physical, 10)]),
+                    },
+                },
+            ],
+        },
+        ItemSet {
+            name: "Shadowstalker",
+            pieces: vec![
+                (Slot::Weapon, "Shadowstalker Kris"),
+                (Slot::Cloak, "Shadowstalker Shroud"),
+                (Slot::Gauntlets, "Shadowstalker Grips"),
+                (Slot::Bracers, "Shadowstalker Wraps"),
+                (Slot::Belt, "Shadowstalker Sash"),
+                (Slot::Boots, "Shadowstalker Treads"),
+            ],
+            bonuses: vec![
+                SetBonus {
+                    pieces: 2,
+                    bonus: StatBonus {
+                        damage: 0,
+                        modifiers: Modifiers { evasion: 10, ..Modifiers::default() },
+                        resistances: Resistances::default(),
+                    },
+                },
+                SetBonus {
+                    pieces: 4,
+                    bonus: StatBonus {
+                        damage: 0,
+                        modifiers: Modifiers { crit_chance: 10, speed: 4, ..Modifiers::default() },
+                        resistances: Resistances::new(&[(DamageType::Shadow, 15)]),
+                    },
+                },
+                SetBonus {
+                    pieces: 6,
+                    bonus: StatBonus {
+                        damage: 6,
+                        modifiers: Modifiers { crit_multiplier: 50, ..Modifiers::default() },
+                        resistances: Resistances::default(),
+                    },
+                },
+            ],
+        },
+        ItemSet {
+            name: "Archmage",
+            pieces: vec![
+                (Slot::Helmet, "Archmage Circlet"),
+                (Slot::Chestplate, "Archmage Robe"),
+                (Slot::Pauldrons, "Archmage Mantle"),
+                (Slot::Amulet, "Archmage Focus"),
+                (Slot::Ring, "Archmage Signet"),
+                (Slot::Cloak, "Archmage Cape"),
+            ],
+            bonuses: vec![
+                SetBonus {
+                    pieces: 2,
+                    bonus: StatBonus {
+                        damage: 0,
+                        modifiers: Modifiers::default(),
+                        resistances: Resistances::new(&[(DamageType::Fire, 15), (DamageType::Ice, 15), (DamageType::Lightning, 15)]),
+                    },
+                },
+                SetBonus {
+                    pieces: 4,
+                    bonus: StatBonus {
+                        damage: 5,
+                        modifiers: Modifiers::default(),
+                        resistances: Resistances::default(),
+                    },
+                },
+                SetBonus {
+                    pieces: 6,
+                    bonus: StatBonus {
+                        damage: 8,
+                        modifiers: Modifiers { crit_chance: 15, hit_chance: 5, ..Modifiers::default() },
+                        resistances: Resistances::default(),
+                    },
+                },
+            ],
+        },
+    ]
+}
+
+pub fn find_set(name: &str) -> Option<ItemSet> {
+    item_sets().into_iter().find(|set| set.name == name)
+}
+
+/// A random piece of a random set.
+pub fn random_set_piece() -> Item {
+    let mut rng = rand::thread_rng();
+    let sets = item_sets();
+    let set = &sets[rng.gen_range(0..sets.len())];
+    let (slot, name) = set.pieces[rng.gen_range(0..set.pieces.len())];
+    Item {
+        name: name.to_string(),
+        description: format!("Part of the {} set", set.name),
+        damage_boost: rng.gen_range(2..6),
+        slot: Some(slot),
+        set: Some(set.name.to_string()),
+        requirements: Requirements {
+            level: SET_PIECE_LEVEL,
+            ..Requirements::default()
+        },
+        durability: SET_PIECE_DURABILITY,
+        max_durability: SET_PIECE_DURABILITY,
+        ..Item::default()
+    }
+}
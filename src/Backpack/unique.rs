@@ -0,0 +1,146 @@
+use rand::Rng;
+use crate::backpack::backpack::{Item, Modifiers, Slot};
+use crate::backpack::requirements::Requirements;
+use crate::damage::{DamageType, Resistances};
+use crate::status::{OnHitEffect, StatusEffect, StatusKind};
+
+/// Level needed to equip any unique.
+const UNIQUE_LEVEL: i128 = 5;
+/// Uniques are built to last a good deal longer than ordinary gear.
+const UNIQUE_DURABILITY: i128 = 150;
+
+fn unique(name: &str, lore: &str, description: &str, slot: Slot, damage_boost: i128) -> Item {
+    Item {
+        name: name.to_string(),
+        description: description.to_string(),
+        damage_boost,
+        slot: Some(slot),
+        lore: Some(lore.to_string()),
+        unique: true,
+        requirements: Requirements {
+            level: UNIQUE_LEVEL,
+            ..Requirements::default()
+        },
+        durability: UNIQUE_DURABILITY,
+        max_durability: UNIQUE_DURABILITY,
+        ..Item::default()
+    }
+}
+
+/// Hand made items with fixed stats that never come out of the name generator.
+/// Boss loot is kept out of this list, so it only ever drops from its boss.
+pub fn unique_items() -> Vec<Item> {
+    vec![
+        Item {
+            wearer_effect: Some(StatusEffect::new(StatusKind::Regen, 5, 4)),
+            resistances: Resistances::new(&[(DamageType::Shadow, 20)]),
+            ..unique(
+                "Ring of the Undying",
+                "Every owner it has had died of old age, and none of them were old.",
+                "A plain iron band that is always warm",
+                Slot::Ring,
+                2,
+            )
+        },
+        Item {
+            modifiers: Modifiers { evasion: 8, speed: 3, ..Modifiers::default() },
+            resistances: Resistances::new(&[(DamageType::Ice, 40), (DamageType::Fire, 20)]),
+            ..unique(
+                "Frostweave Cloak",
+                "Spun by the witches of the northern pass from snow that never melts.",
+                "A cloak that leaves frost on everything it brushes",
+                Slot::Cloak,
+                3,
+            )
+        },
+        Item {
+            modifiers: Modifiers { crit_chance: 10, ..Modifiers::default() },
+            on_hit: Some(OnHitEffect::new(StatusKind::Stun, 1, 0, 15)),
+            resistances: Resistances::new(&[(DamageType::Lightning, 30)]),
+            ..unique(
+                "Stormcaller",
+                "Its last owner was struck by lightning eleven times and lived.",
+                "An amulet with a storm trapped inside the stone",
+                Slot::Amulet,
+                5,
+            )
+        },
+        Item {
+            modifiers: Modifiers { evasion: 10, speed: 8, ..Modifiers::default() },
+            ..unique(
+                "Boots of the Zephyr",
+                "The courier who wore them delivered a letter before it was written.",
+                "Feather-light boots that barely touch the ground",
+                Slot::Boots,
+                2,
+            )
+        },
+        Item {
+            modifiers: Modifiers { crit_chance: 10, ..Modifiers::default() },
+            on_hit: Some(OnHitEffect::new(StatusKind::Poison, 4, 3, 50)),
+            damage_type: DamageType::Poison,
+            ..unique(
+                "Venomkiss",
+                "The assassin's guild still wants it back.",
+                "A slender dagger that weeps green",
+                Slot::Weapon,
+                8,
+            )
+        },
+    ]
+}
+
+/// Dropped every time Gravelord Mortis is beaten.
+pub fn crown_of_the_bone_king() -> Item {
+    Item {
+        modifiers: Modifiers { crit_chance: 5, ..Modifiers::default() },
+        on_hit: Some(OnHitEffect::new(StatusKind::Bleed, 3, 2, 20)),
+        resistances: Resistances::new(&[(DamageType::Shadow, 30), (DamageType::Poison, 20)]),
+        ..unique(
+            "Crown of the Bone King",
+            "Gravelord Mortis wore it for three hundred years and never once took it off.",
+            "A circlet of fused vertebrae that still whispers orders",
+            Slot::Helmet,
+            6,
+        )
+    }
+}
+
+/// Dropped every time Ignathra is beaten.
+pub fn wyrmfang() -> Item {
+    Item {
+        modifiers: Modifiers { hit_chance: 5, crit_multiplier: 25, ..Modifiers::default() },
+        on_hit: Some(OnHitEffect::new(StatusKind::Burn, 3, 3, 30)),
+        damage_type: DamageType::Fire,
+        resistances: Resistances::new(&[(DamageType::Fire, 25)]),
+        ..unique(
+            "Wyrmfang",
+            "Pulled from the jaw of Ignathra, it has never quite cooled.",
+            "A sword carved from a dragon's tooth, warm to the touch",
+            Slot::Weapon,
+            12,
+        )
+    }
+}
+
+/// Dropped every time Thrag is beaten.
+pub fn bulwark_of_the_mountain() -> Item {
+    Item {
+        modifiers: Modifiers { evasion: 10, ..Modifiers::default() },
+        wearer_effect: Some(StatusEffect::new(StatusKind::Regen, 5, 3)),
+        resistances: Resistances::new(&[(DamageType::Physical, 20), (DamageType::Lightning, 10)]),
+        ..unique(
+            "Bulwark of the Mountain",
+            "Thrag cut it from the peak he was named king of.",
+            "A slab of granite with a handle, impossibly light",
+            Slot::Shield,
+            4,
+        )
+    }
+}
+
+pub fn random_unique() -> Item {
+    let mut items = unique_items();
+    let index = rand::thread_rng().gen_range(0..items.len());
+    items.swap_remove(index)
+}
@@ -0,0 +1,178 @@
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use crate::ability::{AbilityEffect, Targeting};
+use crate::battle::CombatAction;
+use crate::entity::Entity;
+
+/// Decides what a computer controlled combatant does on its turn. `enemies`
+/// is the whole opposing side, and `targets` the indexes into it of those
+/// still in the fight, so neither the dead nor the ones who fled.
+pub trait CombatAi {
+    fn choose_action(&self, actor: &Entity, enemies: &[&Entity], targets: &[usize]) -> CombatAction;
+}
+
+/// Which `CombatAi` an entity uses. Stored on the entity so it survives cloning and saving.
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub enum AiKind {
+    #[default]
+    Aggressive,
+    Defensive,
+    Healer,
+    Coward,
+    Random,
+}
+
+impl AiKind {
+    pub fn strategy(&self) -> Box<dyn CombatAi> {
+        match self {
+            AiKind::Aggressive => Box::new(Aggressive),
+            AiKind::Defensive => Box::new(Defensive),
+            AiKind::Healer => Box::new(Healer),
+            AiKind::Coward => Box::new(Coward),
+            AiKind::Random => Box::new(RandomAi),
+        }
+    }
+}
+
+fn health_percent(entity: &Entity) -> i128 {
+    if entity.max_health > 0 {
+        entity.current_health * 100 / entity.max_health
+    } else {
+        0
+    }
+}
+
+/// The enemy still in the fight closest to falling.
+fn weakest_target(enemies: &[&Entity], targets: &[usize]) -> usize {
+    targets
+        .iter()
+        .copied()
+        .min_by_key(|index| enemies[*index].current_health)
+        .unwrap_or(0)
+}
+
+/// The enemy still in the fight that hits the hardest.
+fn biggest_threat(enemies: &[&Entity], targets: &[usize]) -> usize {
+    targets
+        .iter()
+        .copied()
+        .max_by_key(|index| enemies[*index].get_damage())
+        .unwrap_or(0)
+}
+
+fn random_target(targets: &[usize]) -> usize {
+    if targets.is_empty() {
+        return 0;
+    }
+    targets[rand::thread_rng().gen_range(0..targets.len())]
+}
+
+fn first_consumable(actor: &Entity) -> Option<CombatAction> {
+    actor
+        .backpack
+        .consumables()
+        .first()
+        .map(|item| CombatAction::UseItem(item.name.clone()))
+}
+
+/// The first ready, affordable ability with the given targeting that has an
+/// effect matching `wanted`, aimed at `target`.
+fn usable_ability(actor: &Entity, targeting: Targeting, wanted: fn(&AbilityEffect) -> bool, target: usize) -> Option<CombatAction> {
+    actor
+        .abilities
+        .iter()
+        .position(|ability| {
+            ability.is_ready()
+                && ability.cost <= actor.current_mana
+                && ability.targeting == targeting
+                && ability.effects.iter().any(wanted)
+        })
+        .map(|index| CombatAction::UseAbility(index, target))
+}
+
+fn is_damage(effect: &AbilityEffect) -> bool {
+    matches!(effect, AbilityEffect::Damage { .. } | AbilityEffect::Status(_))
+}
+
+fn is_healing(effect: &AbilityEffect) -> bool {
+    matches!(effect, AbilityEffect::Heal { .. })
+}
+
+/// Goes for the weakest enemy, using damaging abilities whenever they are ready.
+pub struct Aggressive;
+
+impl CombatAi for Aggressive {
+    fn choose_action(&self, actor: &Entity, enemies: &[&Entity], targets: &[usize]) -> CombatAction {
+        let target = weakest_target(enemies, targets);
+        usable_ability(actor, Targeting::Enemy, is_damage, target).unwrap_or(CombatAction::Attack(target))
+    }
+}
+
+/// Guards more often the more hurt it is, and whenever one hit would take
+/// a big bite out of its health. Strikes back at the biggest threat.
+pub struct Defensive;
+
+impl CombatAi for Defensive {
+    fn choose_action(&self, actor: &Entity, enemies: &[&Entity], targets: &[usize]) -> CombatAction {
+        let mut rng = rand::thread_rng();
+        let target = biggest_threat(enemies, targets);
+        let threat = enemies.get(target).map_or(0, |enemy| enemy.get_damage());
+        let defend_chance = if threat * 4 >= actor.current_health {
+            60
+        } else if health_percent(actor) < 50 {
+            40
+        } else {
+            15
+        };
+        if rng.gen_range(0..100) < defend_chance {
+            CombatAction::Defend
+        } else {
+            CombatAction::Attack(target)
+        }
+    }
+}
+
+/// Heals itself with spells or potions as soon as it is wounded.
+pub struct Healer;
+
+impl CombatAi for Healer {
+    fn choose_action(&self, actor: &Entity, _enemies: &[&Entity], targets: &[usize]) -> CombatAction {
+        let target = random_target(targets);
+        if health_percent(actor) < 60 {
+            if let Some(action) = usable_ability(actor, Targeting::Caster, is_healing, target).or_else(|| first_consumable(actor)) {
+                return action;
+            }
+        }
+        usable_ability(actor, Targeting::Enemy, is_damage, target).unwrap_or(CombatAction::Attack(target))
+    }
+}
+
+/// Picks on the weakest enemy until badly hurt, then runs.
+pub struct Coward;
+
+impl CombatAi for Coward {
+    fn choose_action(&self, actor: &Entity, enemies: &[&Entity], targets: &[usize]) -> CombatAction {
+        if health_percent(actor) < 30 {
+            CombatAction::Flee
+        } else {
+            CombatAction::Attack(weakest_target(enemies, targets))
+        }
+    }
+}
+
+/// Picks any action it can take against any enemy.
+pub struct RandomAi;
+
+impl CombatAi for RandomAi {
+    fn choose_action(&self, actor: &Entity, _enemies: &[&Entity], targets: &[usize]) -> CombatAction {
+        let mut rng = rand::thread_rng();
+        let target = random_target(targets);
+        match rng.gen_range(0..100) {
+            0..=49 => CombatAction::Attack(target),
+            50..=59 => usable_ability(actor, Targeting::Enemy, is_damage, target).unwrap_or(CombatAction::Attack(target)),
+            60..=79 => CombatAction::Defend,
+            80..=94 => first_consumable(actor).unwrap_or(CombatAction::Attack(target)),
+            _ => CombatAction::Flee,
+        }
+    }
+}
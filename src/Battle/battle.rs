@@ -1,92 +1,820 @@
+use rand::Rng;
+use std::collections::BTreeMap;
 use std::fmt;
+use crate::ability::{AbilityEffect, Targeting};
+use crate::backpack::crafting::Material;
 use crate::entity::Entity;
+use crate::status::StatusTick;
 
+/// Upper bound on rounds so two harmless sides don't fight forever.
+const MAX_ROUNDS: i128 = 100;
+/// Flee chance in percent when both sides are the same level.
+const BASE_FLEE_CHANCE: i128 = 50;
+/// Flee chance gained or lost per level of difference.
+const FLEE_CHANCE_PER_LEVEL: i128 = 10;
+/// Flee chance gained or lost per point of speed difference.
+const FLEE_CHANCE_PER_SPEED: i128 = 3;
+/// Cap on the chance in percent of a faster combatant acting twice in a row.
+const MAX_EXTRA_TURN_CHANCE: i128 = 50;
+/// Defending divides incoming damage by this until the defender's next turn.
+const DEFEND_DIVISOR: i128 = 2;
+
+/// How a battle ended, so the caller can apply its consequences.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BattleResult {
+    AttackerWon,
+    DefenderWon,
+    Stalemate,
+    /// The player led their party out of the fight.
+    Fled,
+    /// Every defender still standing ran away before any of them fell.
+    DefenderFled,
+}
+
+/// Something a combatant can do on its turn. Targets are indexes into the
+/// opposing side.
+#[derive(Debug, Clone, PartialEq)]
+pub enum CombatAction {
+    Attack(usize),
+    Defend,
+    /// Use up a consumable from the backpack, by item name.
+    UseItem(String),
+    /// Use a learned ability by index, aimed at a target when it hits enemies.
+    UseAbility(usize, usize),
+    Flee,
+}
+
+/// Whose move the battle is waiting for.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BattleState {
+    PlayerTurn,
+    /// A computer controlled combatant on either side is up.
+    AiTurn,
+    Finished(BattleResult),
+}
+
+/// XP and gold taken from the fallen and shared among the winners.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Loot {
+    pub xp: i128,
+    pub gold: i128,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Side {
+    Attacker,
+    Defender,
+}
+
+impl Side {
+    fn other(&self) -> Side {
+        match self {
+            Side::Attacker => Side::Defender,
+            Side::Defender => Side::Attacker,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct CombatantId {
+    side: Side,
+    index: usize,
+}
+
+/// The player is always the first attacker.
+const PLAYER: CombatantId = CombatantId {
+    side: Side::Attacker,
+    index: 0,
+};
+
+#[derive(Debug)]
+struct Combatant<'a> {
+    entity: &'a mut Entity,
+    defending: bool,
+    fled: bool,
+}
+
+impl<'a> Combatant<'a> {
+    fn new(entity: &'a mut Entity) -> Self {
+        Combatant {
+            entity,
+            defending: false,
+            fled: false,
+        }
+    }
+
+    /// Still alive and still on the field.
+    fn is_active(&self) -> bool {
+        self.entity.is_alive() && !self.fled
+    }
+}
+
+/// A fight between the player's party (the attackers) and a group of
+/// defenders, advanced one action at a time by the UI. Everyone but the
+/// player is controlled by their `CombatAi`.
 #[derive(Debug)]
 pub struct Battle<'a> {
-    attacker: &'a mut Entity,
-    defender: &'a mut Entity,
+    attackers: Vec<Combatant<'a>>,
+    defenders: Vec<Combatant<'a>>,
+    state: BattleState,
+    rounds: i128,
+    /// Everyone standing at the start of the round, fastest first.
+    turn_order: Vec<CombatantId>,
+    turn: usize,
+    extra_turn_taken: bool,
+    loot: Loot,
 }
 
 impl<'a> Battle<'a> {
-    pub fn new(attacker: &'a mut Entity, defender: &'a mut Entity) -> Self {
-        Self { attacker, defender }
+    /// Sets the battle up without doing anything yet, `start` opens the fight.
+    pub fn new(attackers: Vec<&'a mut Entity>, defenders: Vec<&'a mut Entity>) -> Self {
+        Self {
+            attackers: attackers.into_iter().map(Combatant::new).collect(),
+            defenders: defenders.into_iter().map(Combatant::new).collect(),
+            state: BattleState::PlayerTurn,
+            rounds: 1,
+            turn_order: Vec::new(),
+            turn: 0,
+            extra_turn_taken: false,
+            loot: Loot::default(),
+        }
+    }
+
+    /// Puts on everyone's equipment effects, works out the turn order and
+    /// begins the first turn.
+    pub fn start(&mut self) {
+        for combatant in self.attackers.iter_mut().chain(self.defenders.iter_mut()) {
+            let entity = &mut *combatant.entity;
+            for effect in entity.backpack.equipped_wearer_effects() {
+                println!("{}'s equipment inflicts {} on them", entity.name, effect);
+                entity.apply_status(effect);
+            }
+        }
+
+        // Round 1 begins like any other, with a tick of whatever is already on everyone
+        self.tick_status_effects();
+        if let Some(finished) = self.check_finished() {
+            self.state = finished;
+            return;
+        }
+
+        self.build_turn_order();
+        if let Some(first) = self.turn_order.first() {
+            println!("{} is the fastest and acts first", self.combatant(*first).entity.name);
+        }
+        self.state = self.begin_turn();
+    }
+
+    pub fn state(&self) -> BattleState {
+        self.state
+    }
+
+    pub fn player(&self) -> &Entity {
+        &*self.combatant(PLAYER).entity
+    }
+
+    /// The player's party, player first.
+    pub fn allies(&self) -> Vec<&Entity> {
+        self.entities(Side::Attacker)
+    }
+
+    pub fn enemies(&self) -> Vec<&Entity> {
+        self.entities(Side::Defender)
+    }
+
+    /// Indexes of the enemies the player can still aim at.
+    pub fn targets(&self) -> Vec<usize> {
+        (0..self.defenders.len())
+            .filter(|index| self.defenders[*index].is_active())
+            .collect()
+    }
+
+    /// What the winners took, once the battle is over.
+    pub fn loot(&self) -> Loot {
+        self.loot
+    }
+
+    /// Resolves the player's action. An action that can't be taken returns an
+    /// error and leaves it the player's turn.
+    pub fn player_action(&mut self, action: CombatAction) -> Result<BattleState, String> {
+        if self.state != BattleState::PlayerTurn {
+            return Err("It is not the player's turn.".to_string());
+        }
+        self.state = self.resolve(PLAYER, action)?;
+        Ok(self.state)
+    }
+
+    /// Lets the current combatant's `CombatAi` pick and resolve its action.
+    pub fn ai_turn(&mut self) -> BattleState {
+        if self.state != BattleState::AiTurn {
+            return self.state;
+        }
+
+        let id = self.current();
+        let action = {
+            let actor = &*self.combatant(id).entity;
+            let opponents = self.entities(id.side.other());
+            actor.ai.strategy().choose_action(actor, &opponents, &self.active_targets(id.side))
+        };
+        self.state = match self.resolve(id, action) {
+            Ok(state) => state,
+            Err(err) => {
+                println!("{}", err);
+                let target = self.active_targets(id.side).first().copied().unwrap_or(0);
+                self.resolve(id, CombatAction::Attack(target))
+                    .unwrap_or_else(|_| self.after_action(id, false))
+            }
+        };
+        self.state
+    }
+
+    /// Chance in percent that the player's party gets away, based on relative level and speed.
+    pub fn flee_chance(&self) -> i128 {
+        self.flee_chance_for(PLAYER)
+    }
+
+    fn entities(&self, side: Side) -> Vec<&Entity> {
+        self.side(side).iter().map(|combatant| &*combatant.entity).collect()
+    }
+
+    fn side(&self, side: Side) -> &Vec<Combatant<'a>> {
+        match side {
+            Side::Attacker => &self.attackers,
+            Side::Defender => &self.defenders,
+        }
+    }
+
+    fn combatant(&self, id: CombatantId) -> &Combatant<'a> {
+        &self.side(id.side)[id.index]
     }
 
-    pub fn attack(&mut self) {
-        let rounds = self.get_max_rounds();
-        let attacker_damage = rounds.max_attacker_rounds * self.attacker.get_damage();
-        let defender_damage = rounds.max_defender_rounds * self.defender.get_damage();
+    fn combatant_mut(&mut self, id: CombatantId) -> &mut Combatant<'a> {
+        match id.side {
+            Side::Attacker => &mut self.attackers[id.index],
+            Side::Defender => &mut self.defenders[id.index],
+        }
+    }
+
+    /// The actor and an opponent of theirs, borrowed together.
+    fn pair_mut(&mut self, id: CombatantId, target: usize) -> (&mut Combatant<'a>, &mut Combatant<'a>) {
+        match id.side {
+            Side::Attacker => (&mut self.attackers[id.index], &mut self.defenders[target]),
+            Side::Defender => (&mut self.defenders[id.index], &mut self.attackers[target]),
+        }
+    }
 
+    fn current(&self) -> CombatantId {
+        self.turn_order[self.turn]
+    }
+
+    /// Indexes of the opponents of `side` that can still be targeted.
+    fn active_targets(&self, side: Side) -> Vec<usize> {
+        let opponents = self.side(side.other());
+        (0..opponents.len())
+            .filter(|index| opponents[*index].is_active())
+            .collect()
+    }
+
+    fn check_target(&self, id: CombatantId, target: usize) -> Result<(), String> {
+        if self.active_targets(id.side).contains(&target) {
+            Ok(())
+        } else {
+            Err("That target can't be attacked.".to_string())
+        }
+    }
+
+    /// The standing opponent with the highest value of `stat`.
+    fn strongest_opponent(&self, side: Side, stat: fn(&Entity) -> i128) -> Option<&Entity> {
+        self.side(side.other())
+            .iter()
+            .filter(|combatant| combatant.is_active())
+            .map(|combatant| &*combatant.entity)
+            .max_by_key(|entity| stat(entity))
+    }
+
+    fn flee_chance_for(&self, id: CombatantId) -> i128 {
+        let actor = &*self.combatant(id).entity;
+        let level = self.strongest_opponent(id.side, |entity| entity.level).map_or(0, |entity| entity.level);
+        let speed = self.strongest_opponent(id.side, Entity::get_speed).map_or(0, |entity| entity.get_speed());
+        (BASE_FLEE_CHANCE
+            + (actor.level - level) * FLEE_CHANCE_PER_LEVEL
+            + (actor.get_speed() - speed) * FLEE_CHANCE_PER_SPEED)
+            .clamp(10, 90)
+    }
+
+    /// Chance in percent of acting again straight away, growing with how much
+    /// faster the actor is than the fastest opponent still standing.
+    fn extra_turn_chance(&self, id: CombatantId) -> i128 {
+        let actor_speed = self.combatant(id).entity.get_speed();
+        let Some(fastest) = self.strongest_opponent(id.side, Entity::get_speed) else {
+            return 0;
+        };
+        let target_speed = fastest.get_speed();
+        if actor_speed <= target_speed || actor_speed <= 0 {
+            return 0;
+        }
+        ((actor_speed - target_speed) * 100 / actor_speed).min(MAX_EXTRA_TURN_CHANCE)
+    }
+
+    fn turn_state(&self, id: CombatantId) -> BattleState {
+        if id == PLAYER {
+            BattleState::PlayerTurn
+        } else {
+            BattleState::AiTurn
+        }
+    }
+
+    fn resolve(&mut self, id: CombatantId, action: CombatAction) -> Result<BattleState, String> {
+        match action {
+            CombatAction::Flee => Ok(self.flee(id)),
+            action => {
+                self.perform(id, action)?;
+                Ok(self.after_action(id, true))
+            }
+        }
+    }
+
+    fn perform(&mut self, id: CombatantId, action: CombatAction) -> Result<(), String> {
+        match action {
+            CombatAction::Attack(target) => {
+                self.check_target(id, target)?;
+                let (actor, target) = self.pair_mut(id, target);
+                strike(actor.entity, target.entity, target.defending);
+            }
+            CombatAction::Defend => {
+                let actor = self.combatant_mut(id);
+                println!("{} braces for the next hit", actor.entity.name);
+                actor.defending = true;
+                return Ok(());
+            }
+            CombatAction::UseItem(item_name) => {
+                let actor = &mut *self.combatant_mut(id).entity;
+                let healed = actor.use_item(&item_name)?;
+                println!(
+                    "{} uses {} and recovers {} health ({} health left)",
+                    actor.name,
+                    item_name,
+                    healed,
+                    actor.get_health()
+                );
+            }
+            CombatAction::UseAbility(index, target) => {
+                let targeting = self
+                    .combatant(id)
+                    .entity
+                    .abilities
+                    .get(index)
+                    .map(|ability| ability.targeting);
+                // Abilities aimed at the caster still borrow an opponent, any standing one will do
+                let target = match targeting {
+                    Some(Targeting::Enemy) => {
+                        self.check_target(id, target)?;
+                        target
+                    }
+                    _ => self.active_targets(id.side).first().copied().unwrap_or(0),
+                };
+                let (actor, target) = self.pair_mut(id, target);
+                use_ability(actor.entity, target.entity, index, target.defending)?;
+            }
+            CombatAction::Flee => unreachable!("fleeing is resolved by Battle::flee"),
+        }
+
+        // Acting drops any guard raised on the previous turn
+        self.combatant_mut(id).defending = false;
+        Ok(())
+    }
+
+    /// Tries to escape. The player takes the whole party along, anyone else
+    /// only leaves on their own. A failed attempt gives the hardest hitting
+    /// opponent a free hit.
+    fn flee(&mut self, id: CombatantId) -> BattleState {
+        let chance = self.flee_chance_for(id);
+        if rand::thread_rng().gen_range(0..100) < chance {
+            if id == PLAYER {
+                return BattleState::Finished(self.finish(BattleResult::Fled));
+            }
+            let actor = self.combatant_mut(id);
+            actor.fled = true;
+            println!("{} flees from the battle", actor.entity.name);
+            return self.after_action(id, false);
+        }
+
+        let damage = self
+            .strongest_opponent(id.side, Entity::get_damage)
+            .map_or(0, |entity| entity.get_damage());
+        let actor = self.combatant_mut(id);
+        actor.entity.take_damage(damage);
+        actor.defending = false;
         println!(
-            "{} attacks {}",
-            self.attacker.get_name(),
-            self.defender.get_name()
+            "{} failed to flee and took {} damage ({} health left)",
+            actor.entity.name,
+            damage,
+            actor.entity.get_health()
         );
+        self.after_action(id, false)
+    }
 
-        if attacker_damage > self.defender.get_health() && attacker_damage > defender_damage {
-            println!("{} wins", self.attacker.get_name());
-            self.attacker.add_xp(self.defender.get_xp_drop());
-            // Transfer items from defender's backpack to attacker's backpack
-            self.transfer_items();
-        } else if defender_damage > self.attacker.get_health() && defender_damage > attacker_damage {
-            println!("{} wins", self.defender.get_name());
-        } else {
-            println!(
-                "Attack was unsuccessful between {} and {}",
-                self.attacker.get_name(),
-                self.defender.get_name()
-            );
+    /// Works out whose turn is next once `id` has finished acting.
+    fn after_action(&mut self, id: CombatantId, can_act_again: bool) -> BattleState {
+        self.check_boss_phases();
+        if let Some(finished) = self.check_finished() {
+            return finished;
+        }
+
+        if can_act_again
+            && !self.extra_turn_taken
+            && self.combatant(id).is_active()
+            && rand::thread_rng().gen_range(0..100) < self.extra_turn_chance(id)
+        {
+            self.extra_turn_taken = true;
+            println!("{} is fast enough to act again!", self.combatant(id).entity.name);
+            return self.turn_state(id);
+        }
+        self.extra_turn_taken = false;
+        self.turn += 1;
+        self.begin_turn()
+    }
+
+    /// Hands the turn to the next combatant able to act, skipping the fallen
+    /// and the stunned and starting new rounds as needed.
+    fn begin_turn(&mut self) -> BattleState {
+        loop {
+            while self.turn < self.turn_order.len() && !self.combatant(self.turn_order[self.turn]).is_active() {
+                self.turn += 1;
+            }
+            if self.turn >= self.turn_order.len() {
+                if let Some(finished) = self.end_round() {
+                    return finished;
+                }
+                continue;
+            }
+
+            let id = self.current();
+            let actor = &mut *self.combatant_mut(id).entity;
+            if actor.consume_stun() {
+                println!("{} is stunned and loses their turn", actor.name);
+                self.turn += 1;
+                continue;
+            }
+            return self.turn_state(id);
+        }
+    }
+
+    fn build_turn_order(&mut self) {
+        let mut order: Vec<CombatantId> = Vec::new();
+        for side in [Side::Attacker, Side::Defender] {
+            for index in 0..self.side(side).len() {
+                let id = CombatantId { side, index };
+                if self.combatant(id).is_active() {
+                    order.push(id);
+                }
+            }
         }
+        // Stable sort keeps the attackers ahead on ties
+        order.sort_by_key(|id| -self.combatant(*id).entity.get_speed());
+        self.turn_order = order;
+        self.turn = 0;
     }
 
-    fn transfer_items(&mut self) {
-        let defender = &mut self.defender;
-        let attacker = &mut self.attacker;
-    
-        // Transfer all items from defender's backpack to attacker's backpack
-    
-    let items = defender.backpack.remove_items();
-       for item in items {
-        attacker.backpack.add_item(item.unwrap()); // Unwrap the Option to get the actual item
-       }
+    /// Reports the round and returns the finished state if it was the last one.
+    fn end_round(&mut self) -> Option<BattleState> {
+        let standing: Vec<String> = self
+            .attackers
+            .iter()
+            .chain(self.defenders.iter())
+            .filter(|combatant| combatant.is_active())
+            .map(|combatant| format!("{} has {} health", combatant.entity.name, combatant.entity.get_health()))
+            .collect();
+        println!("Round {}: {}", self.rounds, standing.join(", "));
+        self.rounds += 1;
+
+        if self.rounds > MAX_ROUNDS {
+            return Some(BattleState::Finished(self.finish(BattleResult::Stalemate)));
+        }
+        self.start_round()
     }
 
-    fn get_max_rounds(&self) -> Round {
-        let max_attacker_rounds = self.divide(self.attacker.get_health(), self.defender.get_damage()).unwrap_or(0);
-        let max_defender_rounds = self.divide(self.defender.get_health(), self.attacker.get_damage()).unwrap_or(0);
+    /// Ticks status effects, cooldowns and mana for everyone still fighting
+    /// as a new round begins.
+    fn start_round(&mut self) -> Option<BattleState> {
+        self.tick_status_effects();
+        for combatant in self.attackers.iter_mut().chain(self.defenders.iter_mut()) {
+            if !combatant.is_active() {
+                continue;
+            }
+            combatant.entity.tick_cooldowns();
+            combatant.entity.regenerate_mana();
+        }
+        self.check_boss_phases();
+        if let Some(finished) = self.check_finished() {
+            return Some(finished);
+        }
+        self.build_turn_order();
+        None
+    }
 
-        Round {
-            max_attacker_rounds,
-            max_defender_rounds,
+    /// Applies one tick of every status effect on the combatants still in the fight.
+    fn tick_status_effects(&mut self) {
+        for combatant in self.attackers.iter_mut().chain(self.defenders.iter_mut()) {
+            if !combatant.is_active() {
+                continue;
+            }
+            let entity = &mut *combatant.entity;
+            for tick in entity.tick_status_effects() {
+                report_status_tick(entity, &tick);
+            }
         }
     }
 
-    fn divide(&self, x: i128, y: i128) -> Result<i128, &'static str> {
-        if y != 0 {
-            Ok(x / y)
+    /// Moves any boss that has been worn down far enough into its next phase.
+    fn check_boss_phases(&mut self) {
+        for combatant in self.attackers.iter_mut().chain(self.defenders.iter_mut()) {
+            while let Some(phase) = combatant.entity.next_boss_phase() {
+                println!("{} {}!", combatant.entity.name, phase.announcement);
+                for name in phase.abilities {
+                    println!("{} can now use {}", combatant.entity.name, name);
+                }
+            }
+        }
+    }
+
+    /// A side is beaten once none of its members are left standing.
+    fn check_finished(&mut self) -> Option<BattleState> {
+        let result = if !self.defenders.iter().any(|combatant| combatant.is_active()) {
+            if self.defenders.iter().any(|combatant| !combatant.entity.is_alive()) {
+                BattleResult::AttackerWon
+            } else {
+                BattleResult::DefenderFled
+            }
+        } else if !self.attackers.iter().any(|combatant| combatant.is_active()) {
+            BattleResult::DefenderWon
         } else {
-            Err("Division by zero")
+            return None;
+        };
+        Some(BattleState::Finished(self.finish(result)))
+    }
+
+    fn finish(&mut self, result: BattleResult) -> BattleResult {
+        for combatant in self.attackers.iter_mut().chain(self.defenders.iter_mut()) {
+            combatant.entity.clear_status_effects();
+            combatant.entity.reset_cooldowns();
+        }
+
+        let attackers = names(&self.entities(Side::Attacker));
+        let defenders = names(&self.entities(Side::Defender));
+        match result {
+            BattleResult::AttackerWon => {
+                println!("{} defeated {}", attackers, defenders);
+                self.distribute_loot();
+            }
+            BattleResult::DefenderWon => {
+                println!("{} defeated {}", defenders, attackers);
+            }
+            BattleResult::Stalemate => {
+                println!("Attack was unsuccessful between {} and {}", attackers, defenders);
+            }
+            BattleResult::Fled => {
+                println!("{} fled from {}", attackers, defenders);
+            }
+            BattleResult::DefenderFled => {
+                println!("{} fled from {}", defenders, attackers);
+            }
         }
+        result
+    }
+
+    /// Splits the xp, gold and items of every fallen defender evenly among
+    /// the attackers still standing. Leftovers go to whoever is listed first.
+    fn distribute_loot(&mut self) {
+        let survivors: Vec<usize> = (0..self.attackers.len())
+            .filter(|index| self.attackers[*index].is_active())
+            .collect();
+        if survivors.is_empty() {
+            return;
+        }
+
+        let mut loot = Loot::default();
+        let mut items = Vec::new();
+        let mut materials: BTreeMap<Material, i128> = BTreeMap::new();
+        for fallen in self.defenders.iter_mut().filter(|combatant| !combatant.entity.is_alive()) {
+            loot.xp += fallen.entity.get_xp_drop();
+            loot.gold += fallen.entity.gold;
+            fallen.entity.gold = 0;
+            items.extend(fallen.entity.backpack.remove_items().into_iter().flatten());
+            for (material, amount) in fallen.entity.backpack.take_materials() {
+                *materials.entry(material).or_insert(0) += amount;
+            }
+        }
+
+        let count = survivors.len() as i128;
+        for (position, index) in survivors.iter().enumerate() {
+            let (xp, gold) = if position == 0 {
+                (loot.xp / count + loot.xp % count, loot.gold / count + loot.gold % count)
+            } else {
+                (loot.xp / count, loot.gold / count)
+            };
+            let member = &mut *self.attackers[*index].entity;
+            println!("{} receives {} xp and {} gold", member.name, xp, gold);
+            member.gold += gold;
+            for level_up in member.add_xp(xp) {
+                println!("{}", level_up);
+            }
+        }
+
+        for (position, item) in items.into_iter().enumerate() {
+            let member = &mut *self.attackers[survivors[position % survivors.len()]].entity;
+            println!("{} takes {}", member.get_name(), item.name);
+            member.backpack.add_item(item);
+        }
+
+        // Only the player crafts, so they carry the materials. Survivors are in
+        // party order, so a fallen player leaves them to the next one standing.
+        let carrier = &mut *self.attackers[survivors[0]].entity;
+        for (material, amount) in materials {
+            println!("{} takes {} {}", carrier.get_name(), amount, material);
+            carrier.backpack.add_material(material, amount);
+        }
+        self.loot = loot;
     }
 }
 
-impl<'a> fmt::Display for Battle<'a> {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "Battle between {} and {}", self.attacker.name, self.defender.name)
+/// Chance in percent that an attack connects, never a sure thing either way.
+fn hit_chance(actor: &Entity, target: &Entity) -> i128 {
+    (actor.modifiers.hit_chance - target.modifiers.evasion).clamp(5, 95)
+}
+
+/// Rolls to hit, for damage and for a critical, then applies and reports the result.
+fn strike(actor: &mut Entity, target: &mut Entity, target_defending: bool) {
+    let mut rng = rand::thread_rng();
+    if rng.gen_range(0..100) >= hit_chance(actor, target) {
+        println!("{} attacks {} but misses", actor.name, target.name);
+        return;
+    }
+
+    let mut damage = actor.roll_damage();
+    let critical = rng.gen_range(0..100) < actor.modifiers.crit_chance;
+    if critical {
+        damage = damage * (100 + actor.modifiers.crit_multiplier) / 100;
+    }
+    if target_defending {
+        damage /= DEFEND_DIVISOR;
+    }
+    let damage_type = actor.damage_type();
+    damage = target.resistances.apply(damage, damage_type);
+    target.take_damage(damage);
+
+    let resistance = target.resistances.get(damage_type);
+    let effectiveness = if resistance < 0 {
+        ", it's super effective"
+    } else if resistance > 0 {
+        ", it's resisted"
+    } else {
+        ""
+    };
+    println!(
+        "{} {} {} for {} {} damage{} ({} health left)",
+        actor.name,
+        if critical { "lands a critical hit on" } else { "hits" },
+        target.name,
+        damage,
+        damage_type,
+        effectiveness,
+        target.get_health()
+    );
+
+    for item in actor.wear_gear(true) {
+        println!("{}'s {} breaks!", actor.name, item);
+    }
+    for item in target.wear_gear(false) {
+        println!("{}'s {} breaks!", target.name, item);
+    }
+
+    if target.is_alive() {
+        for effect in actor.on_hit_effects().iter().filter_map(|on_hit| on_hit.roll()) {
+            println!("{} is afflicted with {}", target.name, effect);
+            target.apply_status(effect);
+        }
     }
 }
 
-pub struct Round {
-    max_attacker_rounds: i128,
-    max_defender_rounds: i128,
+/// Spends mana on a learned ability and applies each of its effects.
+fn use_ability(actor: &mut Entity, target: &mut Entity, index: usize, target_defending: bool) -> Result<(), String> {
+    let Some(ability) = actor.abilities.get(index).cloned() else {
+        return Err(format!("{} doesn't know that ability.", actor.name));
+    };
+    if !ability.is_ready() {
+        return Err(format!("{} is ready in {} rounds.", ability.name, ability.cooldown_remaining));
+    }
+    if actor.current_mana < ability.cost {
+        return Err(format!(
+            "Not enough mana for {} ({}/{}).",
+            ability.name, actor.current_mana, ability.cost
+        ));
+    }
+    actor.current_mana -= ability.cost;
+    actor.abilities[index].cooldown_remaining = ability.cooldown;
+    println!("{} uses {}", actor.name, ability.name);
+
+    let caster_damage = actor.get_damage();
+    let (recipient, defending) = match ability.targeting {
+        Targeting::Enemy => (&mut *target, target_defending),
+        Targeting::Caster => (&mut *actor, false),
+    };
+    let mut dealt_damage = false;
+    for effect in &ability.effects {
+        match effect {
+            AbilityEffect::Damage { power, damage_type } => {
+                let mut damage = caster_damage * power / 100;
+                if defending {
+                    damage /= DEFEND_DIVISOR;
+                }
+                damage = recipient.resistances.apply(damage, *damage_type);
+                recipient.take_damage(damage);
+                println!(
+                    "{} takes {} {} damage ({} health left)",
+                    recipient.name,
+                    damage,
+                    damage_type,
+                    recipient.get_health()
+                );
+                dealt_damage = true;
+            }
+            AbilityEffect::Heal { percent } => {
+                let healed = recipient.heal(recipient.max_health * percent / 100);
+                println!(
+                    "{} recovers {} health ({} health left)",
+                    recipient.name,
+                    healed,
+                    recipient.get_health()
+                );
+            }
+            AbilityEffect::Status(status) => {
+                if recipient.is_alive() {
+                    println!("{} is afflicted with {}", recipient.name, status);
+                    recipient.apply_status(*status);
+                }
+            }
+        }
+    }
+
+    // Damaging abilities wear gear down just like a plain hit
+    if dealt_damage {
+        let struck = match ability.targeting {
+            Targeting::Enemy => target,
+            Targeting::Caster => &mut *actor,
+        };
+        for item in struck.wear_gear(false) {
+            println!("{}'s {} breaks!", struck.name, item);
+        }
+        for item in actor.wear_gear(true) {
+            println!("{}'s {} breaks!", actor.name, item);
+        }
+    }
+    Ok(())
+}
+
+fn report_status_tick(entity: &Entity, tick: &StatusTick) {
+    if tick.health_change > 0 {
+        println!(
+            "{} takes {} damage from {} ({} health left)",
+            entity.name,
+            tick.health_change,
+            tick.kind,
+            entity.get_health()
+        );
+    } else if tick.health_change < 0 {
+        println!(
+            "{} recovers {} health from {} ({} health left)",
+            entity.name,
+            -tick.health_change,
+            tick.kind,
+            entity.get_health()
+        );
+    }
+    if tick.expired {
+        println!("{}'s {} wore off", entity.name, tick.kind);
+    }
+}
+
+/// Names joined for battle messages, e.g. "Ana, Bo and Cy".
+fn names(entities: &[&Entity]) -> String {
+    match entities.split_last() {
+        None => String::new(),
+        Some((last, [])) => last.name.clone(),
+        Some((last, rest)) => {
+            let rest: Vec<&str> = rest.iter().map(|entity| entity.name.as_str()).collect();
+            format!("{} and {}", rest.join(", "), last.name)
+        }
+    }
 }
 
-impl fmt::Display for Round {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+impl<'a> fmt::Display for Battle<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(
             f,
-            "Attacker Max Rounds: {}, Defender Max Rounds: {}",
-            self.max_attacker_rounds, self.max_defender_rounds
+            "Battle between {} and {}",
+            names(&self.entities(Side::Attacker)),
+            names(&self.entities(Side::Defender))
         )
     }
 }
@@ -0,0 +1,92 @@
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Default, Serialize, Deserialize)]
+pub enum DamageType {
+    #[default]
+    Physical,
+    Fire,
+    Ice,
+    Lightning,
+    Poison,
+    Holy,
+    Shadow,
+}
+
+impl DamageType {
+    pub const ELEMENTS: [DamageType; 3] = [DamageType::Fire, DamageType::Ice, DamageType::Lightning];
+
+    pub fn random_element() -> DamageType {
+        let mut rng = rand::thread_rng();
+        Self::ELEMENTS[rng.gen_range(0..Self::ELEMENTS.len())]
+    }
+}
+
+impl fmt::Display for DamageType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let type_str = match self {
+            DamageType::Physical => "Physical",
+            DamageType::Fire => "Fire",
+            DamageType::Ice => "Ice",
+            DamageType::Lightning => "Lightning",
+            DamageType::Poison => "Poison",
+            DamageType::Holy => "Holy",
+            DamageType::Shadow => "Shadow",
+        };
+        write!(f, "{}", type_str)
+    }
+}
+
+/// Percentage of each damage type shrugged off. Negative values are weaknesses
+/// that make the damage hurt more.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Resistances(BTreeMap<DamageType, i128>);
+
+impl Resistances {
+    pub fn new(values: &[(DamageType, i128)]) -> Self {
+        let mut resistances = Resistances::default();
+        for (damage_type, percent) in values {
+            resistances.add(*damage_type, *percent);
+        }
+        resistances
+    }
+
+    pub fn get(&self, damage_type: DamageType) -> i128 {
+        self.0.get(&damage_type).copied().unwrap_or(0)
+    }
+
+    pub fn add(&mut self, damage_type: DamageType, percent: i128) {
+        *self.0.entry(damage_type).or_insert(0) += percent;
+    }
+
+    pub fn combine(&mut self, other: &Resistances) {
+        for (damage_type, percent) in &other.0 {
+            self.add(*damage_type, *percent);
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.values().all(|percent| *percent == 0)
+    }
+
+    /// Scales incoming damage. Resistance is capped at full immunity and
+    /// weakness at double damage.
+    pub fn apply(&self, damage: i128, damage_type: DamageType) -> i128 {
+        let resistance = self.get(damage_type).clamp(-100, 100);
+        (damage * (100 - resistance) / 100).max(0)
+    }
+}
+
+impl fmt::Display for Resistances {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let parts: Vec<String> = self
+            .0
+            .iter()
+            .filter(|(_, percent)| **percent != 0)
+            .map(|(damage_type, percent)| format!("{:+}% {} Resist", percent, damage_type))
+            .collect();
+        write!(f, "{}", parts.join(", "))
+    }
+}
@@ -1 +1,3 @@
-pub mod battle;
\ No newline at end of file
+pub mod ai;
+pub mod battle;
+pub mod damage;
\ No newline at end of file
@@ -0,0 +1,268 @@
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use crate::damage::DamageType;
+use crate::status::{StatusEffect, StatusKind};
+
+/// Who an ability lands on.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum Targeting {
+    Enemy,
+    Caster,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum AbilityEffect {
+    /// Deals a percentage of the caster's damage stat as the given type.
+    Damage { power: i128, damage_type: DamageType },
+    /// Restores a percentage of the target's max health.
+    Heal { percent: i128 },
+    Status(StatusEffect),
+}
+
+impl fmt::Display for AbilityEffect {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AbilityEffect::Damage { power, damage_type } => write!(f, "{}% {} damage", power, damage_type),
+            AbilityEffect::Heal { percent } => write!(f, "heals {}% health", percent),
+            AbilityEffect::Status(effect) => write!(f, "{}", effect),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Ability {
+    pub name: String,
+    pub description: String,
+    /// Mana spent on each use.
+    pub cost: i128,
+    /// Rounds before the ability can be used again.
+    pub cooldown: i128,
+    pub targeting: Targeting,
+    pub effects: Vec<AbilityEffect>,
+    /// Rounds left until it is ready, reset when a battle ends.
+    #[serde(default)]
+    pub cooldown_remaining: i128,
+}
+
+impl Ability {
+    fn new(name: &str, description: &str, cost: i128, cooldown: i128, targeting: Targeting, effects: Vec<AbilityEffect>) -> Self {
+        Ability {
+            name: name.to_string(),
+            description: description.to_string(),
+            cost,
+            cooldown,
+            targeting,
+            effects,
+            cooldown_remaining: 0,
+        }
+    }
+
+    pub fn is_ready(&self) -> bool {
+        self.cooldown_remaining <= 0
+    }
+}
+
+impl fmt::Display for Ability {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let effects: Vec<String> = self.effects.iter().map(|effect| effect.to_string()).collect();
+        write!(
+            f,
+            "{}: {} ({} mana, {} round cooldown, {})",
+            self.name,
+            self.description,
+            self.cost,
+            self.cooldown,
+            effects.join(", ")
+        )?;
+        if !self.is_ready() {
+            write!(f, " [ready in {}]", self.cooldown_remaining)?;
+        }
+        Ok(())
+    }
+}
+
+pub fn ability_catalog() -> Vec<Ability> {
+    vec![
+        Ability::new(
+            "Power Strike",
+            "A heavy blow with everything behind it.",
+            5,
+            2,
+            Targeting::Enemy,
+            vec![AbilityEffect::Damage { power: 200, damage_type: DamageType::Physical }],
+        ),
+        Ability::new(
+            "Fireball",
+            "Hurls a ball of flame that sets the target alight.",
+            8,
+            3,
+            Targeting::Enemy,
+            vec![
+                AbilityEffect::Damage { power: 150, damage_type: DamageType::Fire },
+                AbilityEffect::Status(StatusEffect::new(StatusKind::Burn, 3, 3)),
+            ],
+        ),
+        Ability::new(
+            "Frost Bolt",
+            "A shard of ice that freezes the target in place.",
+            6,
+            4,
+            Targeting::Enemy,
+            vec![
+                AbilityEffect::Damage { power: 120, damage_type: DamageType::Ice },
+                AbilityEffect::Status(StatusEffect::new(StatusKind::Stun, 1, 0)),
+            ],
+        ),
+        Ability::new(
+            "Mend",
+            "Closes wounds with a quick prayer.",
+            6,
+            3,
+            Targeting::Caster,
+            vec![AbilityEffect::Heal { percent: 30 }],
+        ),
+        Ability::new(
+            "Envenom",
+            "A poisoned strike that keeps hurting.",
+            4,
+            2,
+            Targeting::Enemy,
+            vec![
+                AbilityEffect::Damage { power: 80, damage_type: DamageType::Poison },
+                AbilityEffect::Status(StatusEffect::new(StatusKind::Poison, 4, 2)),
+            ],
+        ),
+        Ability::new(
+            "Smite",
+            "Calls down holy light on the target.",
+            10,
+            3,
+            Targeting::Enemy,
+            vec![AbilityEffect::Damage { power: 180, damage_type: DamageType::Holy }],
+        ),
+        Ability::new(
+            "Rejuvenate",
+            "Slowly restores health over several rounds.",
+            8,
+            5,
+            Targeting::Caster,
+            vec![AbilityEffect::Status(StatusEffect::new(StatusKind::Regen, 4, 3))],
+        ),
+        Ability::new(
+            "Chain Lightning",
+            "A bolt of lightning that tears through armour.",
+            12,
+            4,
+            Targeting::Enemy,
+            vec![AbilityEffect::Damage { power: 250, damage_type: DamageType::Lightning }],
+        ),
+        Ability::new(
+            "Shadow Bolt",
+            "A lance of darkness.",
+            7,
+            2,
+            Targeting::Enemy,
+            vec![AbilityEffect::Damage { power: 140, damage_type: DamageType::Shadow }],
+        ),
+        Ability::new(
+            "Shield Bash",
+            "Slams the target hard enough to daze it.",
+            6,
+            4,
+            Targeting::Enemy,
+            vec![
+                AbilityEffect::Damage { power: 100, damage_type: DamageType::Physical },
+                AbilityEffect::Status(StatusEffect::new(StatusKind::Stun, 1, 0)),
+            ],
+        ),
+        Ability::new(
+            "Arcane Surge",
+            "Unleashes raw magic in a blinding burst.",
+            15,
+            4,
+            Targeting::Enemy,
+            vec![AbilityEffect::Damage { power: 300, damage_type: DamageType::Lightning }],
+        ),
+        Ability::new(
+            "Backstab",
+            "A knife between the ribs that leaves the target bleeding.",
+            6,
+            3,
+            Targeting::Enemy,
+            vec![
+                AbilityEffect::Damage { power: 220, damage_type: DamageType::Physical },
+                AbilityEffect::Status(StatusEffect::new(StatusKind::Bleed, 3, 3)),
+            ],
+        ),
+        // Boss abilities, never taught by levels or scrolls
+        Ability::new(
+            "Bone Storm",
+            "A whirlwind of splintered bone.",
+            8,
+            3,
+            Targeting::Enemy,
+            vec![
+                AbilityEffect::Damage { power: 200, damage_type: DamageType::Shadow },
+                AbilityEffect::Status(StatusEffect::new(StatusKind::Bleed, 3, 4)),
+            ],
+        ),
+        Ability::new(
+            "Dark Pact",
+            "Trades a sliver of the void for stolen life.",
+            10,
+            5,
+            Targeting::Caster,
+            vec![
+                AbilityEffect::Heal { percent: 20 },
+                AbilityEffect::Status(StatusEffect::new(StatusKind::Regen, 3, 5)),
+            ],
+        ),
+        Ability::new(
+            "Inferno",
+            "Engulfs the target in dragonfire.",
+            12,
+            4,
+            Targeting::Enemy,
+            vec![
+                AbilityEffect::Damage { power: 280, damage_type: DamageType::Fire },
+                AbilityEffect::Status(StatusEffect::new(StatusKind::Burn, 3, 5)),
+            ],
+        ),
+        Ability::new(
+            "Earthquake",
+            "Shakes the ground until the target falls.",
+            10,
+            4,
+            Targeting::Enemy,
+            vec![
+                AbilityEffect::Damage { power: 180, damage_type: DamageType::Physical },
+                AbilityEffect::Status(StatusEffect::new(StatusKind::Stun, 1, 0)),
+            ],
+        ),
+    ]
+}
+
+pub fn find_ability(name: &str) -> Option<Ability> {
+    ability_catalog().into_iter().find(|ability| ability.name == name)
+}
+
+/// The ability learned on reaching a level, if any.
+pub fn ability_for_level(level: i128) -> Option<Ability> {
+    let name = match level {
+        2 => "Power Strike",
+        3 => "Mend",
+        5 => "Fireball",
+        7 => "Smite",
+        10 => "Chain Lightning",
+        _ => return None,
+    };
+    find_ability(name)
+}
+
+/// Abilities that can only be learned from scrolls.
+pub fn random_scroll_ability() -> Ability {
+    let names = ["Frost Bolt", "Envenom", "Rejuvenate", "Shadow Bolt"];
+    let index = rand::thread_rng().gen_range(0..names.len());
+    find_ability(names[index]).expect("scroll abilities are in the catalog")
+}
@@ -0,0 +1,178 @@
+use serde::{Deserialize, Serialize};
+use crate::ai::AiKind;
+use crate::backpack::backpack::{Item, Modifiers};
+use crate::backpack::unique::{bulwark_of_the_mountain, crown_of_the_bone_king, wyrmfang};
+use crate::damage::{DamageType, Resistances};
+use crate::status::{OnHitEffect, StatusKind};
+
+/// A boss blocks the way every this many player levels.
+pub const BOSS_LEVEL_INTERVAL: i128 = 5;
+/// Boss health and damage grow by this percentage per player level.
+const SCALING_PER_LEVEL: i128 = 10;
+
+/// A stage of a boss fight, entered once the boss drops to a share of its health.
+#[derive(Debug, Clone)]
+pub struct BossPhase {
+    /// Entered at or below this percentage of max health.
+    pub health_percent: i128,
+    pub announcement: &'static str,
+    /// Percentage added to the boss's base damage.
+    pub damage_bonus: i128,
+    pub modifiers: Modifiers,
+    /// Abilities from the catalog learned on entering the phase.
+    pub abilities: Vec<&'static str>,
+    pub ai: Option<AiKind>,
+}
+
+#[derive(Debug, Clone)]
+pub struct BossTemplate {
+    pub name: &'static str,
+    pub title: &'static str,
+    pub health: i128,
+    pub damage: i128,
+    pub xp_drop: i128,
+    pub gold: i128,
+    pub speed: i128,
+    pub attack_type: DamageType,
+    pub resistances: Resistances,
+    pub ai: AiKind,
+    pub on_hit: Option<OnHitEffect>,
+    pub abilities: Vec<&'static str>,
+    /// In the order they are entered, highest health threshold first.
+    pub phases: Vec<BossPhase>,
+    /// Makes the unique item dropped every time the boss is beaten.
+    pub loot: fn() -> Item,
+}
+
+/// Which boss an entity is and how far into the fight it has got.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BossState {
+    pub name: String,
+    /// Number of phases entered so far.
+    pub phase: usize,
+}
+
+pub fn boss_templates() -> Vec<BossTemplate> {
+    vec![
+        BossTemplate {
+            name: "Gravelord Mortis",
+            title: "the Bone King",
+            health: 180,
+            damage: 9,
+            xp_drop: 150,
+            gold: 120,
+            speed: 9,
+            attack_type: DamageType::Shadow,
+            resistances: Resistances::new(&[(DamageType::Shadow, 50), (DamageType::Poison, 100), (DamageType::Holy, -50)]),
+            ai: AiKind::Aggressive,
+            on_hit: Some(OnHitEffect::new(StatusKind::Bleed, 3, 3, 25)),
+            abilities: vec!["Shadow Bolt"],
+            phases: vec![
+                BossPhase {
+                    health_percent: 60,
+                    announcement: "shatters into a storm of bones and reforms, angrier than before",
+                    damage_bonus: 25,
+                    modifiers: Modifiers::default(),
+                    abilities: vec!["Bone Storm"],
+                    ai: None,
+                },
+                BossPhase {
+                    health_percent: 25,
+                    announcement: "makes a pact with the dark to cling on to its existence",
+                    damage_bonus: 25,
+                    modifiers: Modifiers { speed: 4, ..Modifiers::default() },
+                    abilities: vec!["Dark Pact"],
+                    ai: Some(AiKind::Healer),
+                },
+            ],
+            loot: crown_of_the_bone_king,
+        },
+        BossTemplate {
+            name: "Ignathra",
+            title: "the Ember Wyrm",
+            health: 220,
+            damage: 10,
+            xp_drop: 200,
+            gold: 180,
+            speed: 12,
+            attack_type: DamageType::Fire,
+            resistances: Resistances::new(&[(DamageType::Fire, 75), (DamageType::Ice, -50)]),
+            ai: AiKind::Aggressive,
+            on_hit: Some(OnHitEffect::new(StatusKind::Burn, 2, 4, 30)),
+            abilities: vec!["Fireball"],
+            phases: vec![
+                BossPhase {
+                    health_percent: 50,
+                    announcement: "spreads its wings and the air itself ignites",
+                    damage_bonus: 20,
+                    modifiers: Modifiers { speed: 5, evasion: 10, ..Modifiers::default() },
+                    abilities: vec!["Inferno"],
+                    ai: None,
+                },
+                BossPhase {
+                    health_percent: 20,
+                    announcement: "roars in fury, its scales glowing white hot",
+                    damage_bonus: 50,
+                    modifiers: Modifiers { crit_chance: 15, ..Modifiers::default() },
+                    abilities: vec![],
+                    ai: None,
+                },
+            ],
+            loot: wyrmfang,
+        },
+        BossTemplate {
+            name: "Thrag",
+            title: "the Mountain King",
+            health: 300,
+            damage: 12,
+            xp_drop: 260,
+            gold: 250,
+            speed: 6,
+            attack_type: DamageType::Physical,
+            resistances: Resistances::new(&[(DamageType::Physical, 30), (DamageType::Lightning, -30)]),
+            ai: AiKind::Defensive,
+            on_hit: Some(OnHitEffect::new(StatusKind::Stun, 1, 0, 15)),
+            abilities: vec!["Power Strike"],
+            phases: vec![
+                BossPhase {
+                    health_percent: 50,
+                    announcement: "stamps the ground hard enough to split the cave floor",
+                    damage_bonus: 20,
+                    modifiers: Modifiers::default(),
+                    abilities: vec!["Earthquake"],
+                    ai: Some(AiKind::Aggressive),
+                },
+                BossPhase {
+                    health_percent: 25,
+                    announcement: "throws away his shield and swings wildly",
+                    damage_bonus: 60,
+                    modifiers: Modifiers { speed: 6, evasion: -10, ..Modifiers::default() },
+                    abilities: vec![],
+                    ai: None,
+                },
+            ],
+            loot: bulwark_of_the_mountain,
+        },
+    ]
+}
+
+pub fn find_boss(name: &str) -> Option<BossTemplate> {
+    boss_templates().into_iter().find(|boss| boss.name == name)
+}
+
+/// Whether the player has reached a milestone whose boss is still waiting.
+pub fn boss_due(level: i128, bosses_defeated: i128) -> bool {
+    level >= (bosses_defeated + 1) * BOSS_LEVEL_INTERVAL
+}
+
+/// The boss guarding the next milestone. They repeat once all have been beaten.
+pub fn next_boss(bosses_defeated: i128) -> BossTemplate {
+    let mut bosses = boss_templates();
+    let index = bosses_defeated.rem_euclid(bosses.len() as i128) as usize;
+    bosses.swap_remove(index)
+}
+
+/// Scales a boss stat to the level of the player facing it.
+pub fn scale_for_level(value: i128, level: i128) -> i128 {
+    value * (100 + (level - 1).max(0) * SCALING_PER_LEVEL) / 100
+}
@@ -1,106 +1,793 @@
 use rand::Rng;
 use rnglib::{Language, RNG};
+use serde::{Deserialize, Serialize};
 use std::fmt;
-use crate::backpack::backpack::{Backpack, Slot, Item};
+use crate::ability::{ability_for_level, find_ability, Ability};
+use crate::ai::AiKind;
+use crate::backpack::backpack::{Backpack, EquipError, Slot, Item, Modifiers};
+use crate::backpack::crafting::{format_materials, Recipe};
+use crate::backpack::enchanting::{
+    random_enchantment, roll_upgrade, upgrade_cost, Enchantment, ENCHANT_GOLD, ENCHANT_MATERIALS, MAX_UPGRADE_LEVEL,
+};
+use crate::boss::{find_boss, scale_for_level, BossPhase, BossState, BossTemplate};
+use crate::damage::{DamageType, Resistances};
+use crate::skill_tree::{find_talent, talent_totals, Class};
+use crate::status::{OnHitEffect, StatusEffect, StatusKind, StatusTick};
+use crate::template::{random_enemy_template, EnemyTemplate};
 
+/// Max mana gained on every level up.
+const MANA_PER_LEVEL: i128 = 5;
+/// Talent points earned on every level up.
+const TALENT_POINTS_PER_LEVEL: i128 = 1;
+/// Gold per character level it costs to reset the skill tree.
+const RESPEC_COST_PER_LEVEL: i128 = 25;
+
+/// A single level gained by an entity, returned so the UI can announce it.
 #[derive(Debug, Clone)]
+pub struct LevelUp {
+    pub name: String,
+    pub level: i128,
+    pub max_health: i128,
+    pub damage: i128,
+    pub xp_drop: i128,
+    pub xp_threshold: i128,
+    /// Name of the ability unlocked at this level, if any.
+    pub learned: Option<String>,
+    pub talent_points: i128,
+}
+
+impl fmt::Display for LevelUp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} reached level {}! Health: {}, Damage: {}, XP Dropped: {}, Next level at {} XP",
+            self.name, self.level, self.max_health, self.damage, self.xp_drop, self.xp_threshold
+        )?;
+        if let Some(ability) = &self.learned {
+            write!(f, "\n{} learned {}!", self.name, ability)?;
+        }
+        write!(f, "\n{} talent points to spend", self.talent_points)?;
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Entity {
     pub name: String,
     pub age: i32,
-    pub health: i128,
+    /// Only the player has a class; enemies are `None`.
+    #[serde(default)]
+    pub class: Option<Class>,
+    #[serde(default)]
+    pub talent_points: i128,
+    /// Ids of the unlocked talents in this entity's class skill tree.
+    #[serde(default)]
+    pub talents: Vec<String>,
+    /// Abilities that only talents taught, the ones a respec takes away again.
+    #[serde(default)]
+    pub talent_abilities: Vec<String>,
+    pub max_health: i128,
+    pub current_health: i128,
+    pub health_regen: i128,
+    /// Spent on abilities. Regenerates each battle round and each turn out of combat.
+    #[serde(default = "Entity::default_mana")]
+    pub max_mana: i128,
+    #[serde(default = "Entity::default_mana")]
+    pub current_mana: i128,
+    #[serde(default = "Entity::default_mana_regen")]
+    pub mana_regen: i128,
+    #[serde(default)]
+    pub abilities: Vec<Ability>,
     pub damage: i128,
     pub base_damage: i128,
+    /// Hit, evasion and crit stats before equipment.
+    #[serde(default = "Entity::default_base_modifiers")]
+    pub base_modifiers: Modifiers,
+    /// Base modifiers plus everything equipped, kept up to date by `recalculate_stats`.
+    #[serde(default = "Entity::default_base_modifiers")]
+    pub modifiers: Modifiers,
+    /// Damage type of unarmed hits, replaced by the weapon's when one is equipped.
+    #[serde(default)]
+    pub attack_type: DamageType,
+    #[serde(default)]
+    pub base_resistances: Resistances,
+    /// Base resistances plus everything equipped, kept up to date by `recalculate_stats`.
+    #[serde(default)]
+    pub resistances: Resistances,
     pub level: i128,
     pub xp_drop: i128,
     pub xp_stored: i128,
     pub level_up_multiplier: f64,
     pub xp_threshold: i128,
     pub multiplier: f64,
+    pub gold: i128,
+    /// Marks a hardcore (one life) character.
+    #[serde(default)]
+    pub hardcore: bool,
+    /// How this entity picks its actions when the computer controls it.
+    #[serde(default)]
+    pub ai: AiKind,
+    /// Effects currently active, cleared when a battle ends.
+    #[serde(default)]
+    pub status_effects: Vec<StatusEffect>,
+    /// Innate effect inflicted by this entity's hits, on top of any from its items.
+    #[serde(default)]
+    pub on_hit: Option<OnHitEffect>,
+    /// Set on bosses so their phases can be tracked during a fight.
+    #[serde(default)]
+    pub boss: Option<BossState>,
     pub backpack: Backpack,
 }
 
 impl Entity {
     pub fn new() -> Self {
-        let rng = RNG::try_from(&Language::Elven).unwrap();
+        let rng = RNG::from(&Language::Elven);
         let mut rnd_num = rand::thread_rng();
         let damage = rnd_num.gen_range(1..10).into();
+        let health: i128 = rnd_num.gen_range(10..100).into();
+        let mut base_modifiers = Self::default_base_modifiers();
+        base_modifiers.speed = rnd_num.gen_range(5..15);
         Self {
             name: rng.generate_name(),
             age: rnd_num.gen_range(18..100),
-            health: rnd_num.gen_range(10..100).into(),
-            damage: damage,
+            class: None,
+            talent_points: 0,
+            talents: Vec::new(),
+            talent_abilities: Vec::new(),
+            max_health: health,
+            current_health: health,
+            health_regen: Self::regen_for(health),
+            max_mana: Self::default_mana(),
+            current_mana: Self::default_mana(),
+            mana_regen: Self::default_mana_regen(),
+            abilities: Vec::new(),
+            damage,
             base_damage: damage,
+            base_modifiers,
+            modifiers: base_modifiers,
+            attack_type: DamageType::Physical,
+            base_resistances: Resistances::default(),
+            resistances: Resistances::default(),
             level: 1,
             xp_drop: rnd_num.gen_range(1..50).into(),
             xp_stored: 0,
             level_up_multiplier: 1.01,
             xp_threshold: 100,
             multiplier: 1.1,
+            gold: rnd_num.gen_range(0..20).into(),
+            hardcore: false,
+            ai: AiKind::default(),
+            status_effects: Vec::new(),
+            on_hit: None,
+            boss: None,
             backpack: Backpack::new(),
         }
     }
 
+    fn default_mana() -> i128 {
+        20
+    }
+
+    fn default_mana_regen() -> i128 {
+        2
+    }
+
+    fn default_base_modifiers() -> Modifiers {
+        Modifiers {
+            hit_chance: 90,
+            evasion: 5,
+            crit_chance: 5,
+            crit_multiplier: 50,
+            damage_variance: 20,
+            speed: 10,
+        }
+    }
+
+    /// A random enemy drawn from the enemy templates.
+    pub fn new_enemy() -> Self {
+        Self::from_template(&random_enemy_template())
+    }
+
+    pub fn from_template(template: &EnemyTemplate) -> Self {
+        let mut rnd_num = rand::thread_rng();
+        let mut enemy = Self::new();
+        let health = rnd_num.gen_range(template.health.clone());
+        let damage = rnd_num.gen_range(template.damage.clone());
+
+        enemy.name = format!("{} the {}", enemy.name, template.kind);
+        enemy.max_health = health;
+        enemy.current_health = health;
+        enemy.health_regen = Self::regen_for(health);
+        enemy.damage = damage;
+        enemy.base_damage = damage;
+        enemy.xp_drop = rnd_num.gen_range(template.xp_drop.clone());
+        enemy.gold = rnd_num.gen_range(template.gold.clone());
+        enemy.base_modifiers.speed = rnd_num.gen_range(template.speed.clone());
+        enemy.modifiers = enemy.base_modifiers;
+        enemy.attack_type = template.attack_type;
+        enemy.base_resistances = template.resistances.clone();
+        enemy.resistances = template.resistances.clone();
+        enemy.ai = template.ai;
+        enemy.on_hit = template.on_hit;
+        for name in &template.abilities {
+            if let Some(ability) = find_ability(name) {
+                enemy.learn_ability(ability);
+            }
+        }
+        for _ in 0..template.potions {
+            enemy.backpack.add_item(Item::new_random_with_suffix("Potion"));
+        }
+        for (material, amount) in &template.materials {
+            enemy.backpack.add_material(*material, rnd_num.gen_range(amount.clone()));
+        }
+        enemy
+    }
+
+    /// A boss scaled to the level of the player facing it, carrying its unique loot.
+    pub fn from_boss(template: &BossTemplate, level: i128) -> Self {
+        let mut boss = Self::new();
+        let health = scale_for_level(template.health, level);
+        let damage = scale_for_level(template.damage, level);
+
+        boss.name = format!("{} {}", template.name, template.title);
+        boss.age = 500;
+        boss.level = level;
+        boss.max_health = health;
+        boss.current_health = health;
+        boss.health_regen = Self::regen_for(health);
+        boss.max_mana = Self::default_mana() * 3;
+        boss.current_mana = boss.max_mana;
+        boss.damage = damage;
+        boss.base_damage = damage;
+        boss.xp_drop = scale_for_level(template.xp_drop, level);
+        boss.gold = scale_for_level(template.gold, level);
+        boss.base_modifiers.speed = template.speed;
+        boss.modifiers = boss.base_modifiers;
+        boss.attack_type = template.attack_type;
+        boss.base_resistances = template.resistances.clone();
+        boss.resistances = template.resistances.clone();
+        boss.ai = template.ai;
+        boss.on_hit = template.on_hit;
+        for name in &template.abilities {
+            if let Some(ability) = find_ability(name) {
+                boss.learn_ability(ability);
+            }
+        }
+        boss.boss = Some(BossState {
+            name: template.name.to_string(),
+            phase: 0,
+        });
+        boss.backpack.add_item((template.loot)());
+        boss
+    }
+
+    /// Enters the boss's next phase once its health has dropped far enough,
+    /// returning the phase so the battle can announce it.
+    pub fn next_boss_phase(&mut self) -> Option<BossPhase> {
+        let state = self.boss.as_ref()?;
+        let phase = find_boss(&state.name)?.phases.into_iter().nth(state.phase)?;
+        if !self.is_alive() || self.current_health * 100 > self.max_health * phase.health_percent {
+            return None;
+        }
+
+        if let Some(state) = self.boss.as_mut() {
+            state.phase += 1;
+        }
+        self.base_damage += self.base_damage * phase.damage_bonus / 100;
+        self.base_modifiers = self.base_modifiers + phase.modifiers;
+        for name in &phase.abilities {
+            if let Some(ability) = find_ability(name) {
+                self.learn_ability(ability);
+            }
+        }
+        if let Some(ai) = phase.ai {
+            self.ai = ai;
+        }
+        self.refresh_stats();
+        Some(phase)
+    }
+
     pub fn get_name(&mut self) -> &str {
         &self.name
     }
 
     pub fn get_health(&self) -> i128 {
-        self.health
+        self.current_health
+    }
+
+    pub fn is_alive(&self) -> bool {
+        self.current_health > 0
+    }
+
+    pub fn take_damage(&mut self, damage: i128) {
+        self.current_health = (self.current_health - damage.max(0)).max(0);
+    }
+
+    /// Heals up to max health and returns how much was actually restored.
+    pub fn heal(&mut self, amount: i128) -> i128 {
+        let healed = amount.max(0).min(self.max_health - self.current_health);
+        self.current_health += healed;
+        healed
+    }
+
+    /// Out-of-combat regeneration applied once per turn.
+    pub fn regenerate(&mut self) -> i128 {
+        self.heal(self.health_regen)
+    }
+
+    /// Restores mana up to max and returns how much was actually restored.
+    pub fn regenerate_mana(&mut self) -> i128 {
+        let restored = self.mana_regen.max(0).min(self.max_mana - self.current_mana);
+        self.current_mana += restored;
+        restored
+    }
+
+    pub fn restore(&mut self) {
+        self.current_health = self.max_health;
+        self.current_mana = self.max_mana;
+        self.reset_cooldowns();
+    }
+
+    /// Returns false if the ability was already known.
+    pub fn learn_ability(&mut self, ability: Ability) -> bool {
+        if self.abilities.iter().any(|known| known.name == ability.name) {
+            return false;
+        }
+        self.abilities.push(ability);
+        true
+    }
+
+    pub fn tick_cooldowns(&mut self) {
+        for ability in self.abilities.iter_mut() {
+            ability.cooldown_remaining = (ability.cooldown_remaining - 1).max(0);
+        }
+    }
+
+    pub fn reset_cooldowns(&mut self) {
+        for ability in self.abilities.iter_mut() {
+            ability.cooldown_remaining = 0;
+        }
+    }
+
+    pub fn apply_status(&mut self, effect: StatusEffect) {
+        match self.status_effects.iter_mut().find(|active| active.kind == effect.kind) {
+            Some(active) => active.stack(&effect),
+            None => self.status_effects.push(effect),
+        }
+    }
+
+    /// Uses up one turn of stun. Returns false if the entity wasn't stunned.
+    pub fn consume_stun(&mut self) -> bool {
+        let Some(stun) = self.status_effects.iter_mut().find(|effect| effect.kind == StatusKind::Stun) else {
+            return false;
+        };
+        stun.duration -= 1;
+        self.status_effects.retain(|effect| effect.duration > 0);
+        true
+    }
+
+    /// Ticks every effect once at the start of a round. Stun counts down by
+    /// skipped turns instead, see `consume_stun`.
+    pub fn tick_status_effects(&mut self) -> Vec<StatusTick> {
+        let mut ticks = Vec::new();
+        for effect in self.status_effects.iter_mut().filter(|effect| effect.kind != StatusKind::Stun) {
+            effect.duration -= 1;
+            ticks.push(StatusTick {
+                kind: effect.kind,
+                health_change: effect.health_change(),
+                expired: effect.duration <= 0,
+            });
+        }
+        for tick in ticks.iter_mut() {
+            if let Some(damage_type) = tick.kind.damage_type() {
+                tick.health_change = self.resistances.apply(tick.health_change, damage_type);
+            }
+            if tick.health_change > 0 {
+                self.take_damage(tick.health_change);
+            } else {
+                tick.health_change = -self.heal(-tick.health_change);
+            }
+        }
+        self.status_effects.retain(|effect| effect.duration > 0);
+        ticks
+    }
+
+    pub fn clear_status_effects(&mut self) {
+        self.status_effects.clear();
+    }
+
+    /// Active effects as shown in the combat UI, e.g. "Poison x2 (3 rounds)".
+    pub fn status_summary(&self) -> String {
+        self.status_effects
+            .iter()
+            .map(|effect| effect.to_string())
+            .collect::<Vec<String>>()
+            .join(", ")
+    }
+
+    /// Innate on-hit effect plus those of equipped items.
+    pub fn on_hit_effects(&self) -> Vec<OnHitEffect> {
+        self.on_hit
+            .into_iter()
+            .chain(self.backpack.equipped_on_hit_effects())
+            .collect()
+    }
+
+    fn regen_for(max_health: i128) -> i128 {
+        (max_health / 20).max(1)
     }
 
     pub fn get_damage(&self) -> i128 {
         self.damage
     }
 
-    pub fn get_xp_stored(&self) -> i128 {
-        self.xp_stored
+    /// Stores the xp and applies every level it is enough for.
+    pub fn add_xp(&mut self, xp: i128) -> Vec<LevelUp> {
+        self.xp_stored += xp;
+        self.level_up()
     }
 
-    pub fn add_xp(&mut self, xp: i128) {
-        self.xp_stored += xp;
+    pub fn damage_type(&self) -> DamageType {
+        self.backpack.weapon_damage_type().unwrap_or(self.attack_type)
+    }
+
+    pub fn get_speed(&self) -> i128 {
+        self.modifiers.speed
     }
 
     pub fn get_xp_drop(&self) -> i128 {
         self.xp_drop
     }
 
-    pub fn recalculate_damage(&mut self) {
-        // Calculate total damage boost from equipped items
+    pub fn recalculate_stats(&mut self) {
+        self.refresh_stats();
         let total_item_damage: i128 = self.backpack.calculate_total_damage();
+        let set_damage: i128 = self.backpack.active_set_bonuses().iter().map(|bonus| bonus.damage).sum();
 
-        // Update the current damage with the total item damage boost
-        self.damage = self.base_damage + total_item_damage;
-    
         // Print the new damage calculation
-        println!("Your new damage is Base Damage {} + Item Boost {} = {}", self.base_damage, total_item_damage, self.damage);
+        println!(
+            "Your new damage is Base Damage {} + Item Boost {} + Set Bonuses {} + Talents {} = {}",
+            self.base_damage,
+            total_item_damage,
+            set_damage,
+            self.damage - self.base_damage - total_item_damage - set_damage,
+            self.damage
+        );
+        println!("Your combat stats are now {}", self.modifiers);
+        println!("You deal {} damage with resistances {}", self.damage_type(), self.resistances);
     }
 
-    pub fn level_up(&mut self) {
-        let levels = self.xp_stored / self.xp_threshold;
-        if levels > 0 {
-            self.level += levels;
-            self.health = (self.health as f64 * self.multiplier).floor() as i128 + levels;
-            self.xp_drop = (self.xp_drop as f64 * self.multiplier).floor() as i128 + levels;
-            self.damage = (self.damage as f64 * self.multiplier).floor() as i128 + levels;
-            self.xp_stored %= self.xp_threshold;
+    /// Recomputes damage, modifiers and resistances from base stats, equipment and talents.
+    fn refresh_stats(&mut self) {
+        let talents = talent_totals(self.class, &self.talents);
+
+        let set_bonuses = self.backpack.active_set_bonuses();
+
+        self.damage = self.base_damage
+            + self.backpack.calculate_total_damage()
+            + talents.damage
+            + set_bonuses.iter().map(|bonus| bonus.damage).sum::<i128>();
+        self.modifiers = self.base_modifiers
+            + self.backpack.calculate_total_modifiers()
+            + talents.modifiers
+            + set_bonuses.iter().map(|bonus| bonus.modifiers).sum();
+        self.resistances = self.base_resistances.clone();
+        self.resistances.combine(&self.backpack.calculate_total_resistances());
+        self.resistances.combine(&talents.resistances);
+        for bonus in &set_bonuses {
+            self.resistances.combine(&bonus.resistances);
+        }
+    }
+
+    /// Spends talent points on a node of this entity's skill tree.
+    pub fn unlock_talent(&mut self, id: &str) -> Result<(), String> {
+        let Some(class) = self.class else {
+            return Err("Choose a class before spending talent points.".to_string());
+        };
+        let Some(talent) = find_talent(class, id) else {
+            return Err(format!("The {} skill tree has no such talent.", class));
+        };
+        if self.talents.iter().any(|unlocked| unlocked == talent.id) {
+            return Err(format!("{} is already unlocked.", talent.name));
+        }
+        if let Some(missing) = talent.requires.iter().find(|required| !self.talents.iter().any(|unlocked| unlocked == *required)) {
+            let missing_name = find_talent(class, missing).map_or(missing.to_string(), |required| required.name.to_string());
+            return Err(format!("{} requires {} first.", talent.name, missing_name));
+        }
+        if self.talent_points < talent.cost {
+            return Err(format!(
+                "{} costs {} talent points but you have {}.",
+                talent.name, talent.cost, self.talent_points
+            ));
+        }
+
+        self.talent_points -= talent.cost;
+        self.talents.push(talent.id.to_string());
+        let unlocked = talent_totals(Some(class), &[talent.id.to_string()]);
+        self.max_health += unlocked.max_health;
+        self.current_health += unlocked.max_health;
+        self.max_mana += unlocked.max_mana;
+        self.current_mana += unlocked.max_mana;
+        for name in unlocked.abilities {
+            // Abilities already known from scrolls or levels stay the player's own
+            if let Some(ability) = find_ability(name) {
+                if self.learn_ability(ability) {
+                    self.talent_abilities.push(name.to_string());
+                }
+            }
+        }
+        self.refresh_stats();
+        Ok(())
+    }
+
+    pub fn respec_cost(&self) -> i128 {
+        self.level * RESPEC_COST_PER_LEVEL
+    }
+
+    /// Pays gold to lock every talent again and returns the points refunded.
+    pub fn respec(&mut self) -> Result<i128, String> {
+        if self.talents.is_empty() {
+            return Err("You have no talents to reset.".to_string());
+        }
+        let cost = self.respec_cost();
+        if self.gold < cost {
+            return Err(format!("Resetting your talents costs {} gold but you have {}.", cost, self.gold));
+        }
+
+        let Some(class) = self.class else {
+            return Err("Choose a class before spending talent points.".to_string());
+        };
+        let totals = talent_totals(Some(class), &self.talents);
+        let talent_abilities = std::mem::take(&mut self.talent_abilities);
+        let refunded: i128 = self
+            .talents
+            .iter()
+            .filter_map(|id| find_talent(class, id))
+            .map(|talent| talent.cost)
+            .sum();
+
+        self.gold -= cost;
+        self.talent_points += refunded;
+        self.talents.clear();
+        self.max_health = (self.max_health - totals.max_health).max(1);
+        self.current_health = self.current_health.min(self.max_health);
+        self.max_mana = (self.max_mana - totals.max_mana).max(0);
+        self.current_mana = self.current_mana.min(self.max_mana);
+        self.abilities.retain(|ability| !talent_abilities.contains(&ability.name));
+        self.refresh_stats();
+        Ok(refunded)
+    }
+
+    /// Rolls damage within this entity's variance around its damage stat.
+    pub fn roll_damage(&self) -> i128 {
+        let variance = self.modifiers.damage_variance.clamp(0, 100);
+        let roll = rand::thread_rng().gen_range(-variance..=variance);
+        (self.damage * (100 + roll) / 100).max(0)
+    }
+
+    /// Levels up once for every threshold the stored xp crosses. Each level
+    /// consumes its own threshold and the next one is raised before checking again.
+    pub fn level_up(&mut self) -> Vec<LevelUp> {
+        let mut level_ups = Vec::new();
+
+        while self.xp_threshold > 0 && self.xp_stored >= self.xp_threshold {
+            self.xp_stored -= self.xp_threshold;
+            self.level += 1;
+            let max_health = (self.max_health as f64 * self.multiplier).floor() as i128 + 1;
+            self.current_health += max_health - self.max_health;
+            self.max_health = max_health;
+            self.health_regen = Self::regen_for(max_health);
+            self.xp_drop = (self.xp_drop as f64 * self.multiplier).floor() as i128 + 1;
+            self.base_damage = (self.base_damage as f64 * self.multiplier).floor() as i128 + 1;
+            self.refresh_stats();
             self.level_up_multiplier *= 1.0 + (self.level / 100) as f64;
             self.xp_threshold = (self.xp_threshold as f64 * self.level_up_multiplier).ceil() as i128;
+            self.max_mana += MANA_PER_LEVEL;
+            self.current_mana += MANA_PER_LEVEL;
+            self.talent_points += TALENT_POINTS_PER_LEVEL;
+            let learned = ability_for_level(self.level)
+                .filter(|ability| self.learn_ability(ability.clone()))
+                .map(|ability| ability.name);
+
+            level_ups.push(LevelUp {
+                name: self.name.clone(),
+                level: self.level,
+                max_health: self.max_health,
+                damage: self.damage,
+                xp_drop: self.xp_drop,
+                xp_threshold: self.xp_threshold,
+                learned,
+                talent_points: self.talent_points,
+            });
+        }
+
+        level_ups
+    }
 
-            println!(
-                "You have gained {} levels\n\
-                 Your new level is {}\n\
-                 Your new damage is {}\n\
-                 Your new level multiplier is {}\n\
-                 Your new level threshold is {}\n\
-                 Your new health is {}\n\
-                 Your new xp_drop is {}",
-                levels, self.level, self.damage, self.level_up_multiplier, self.xp_threshold, self.health, self.xp_drop
-            );
+    /// Uses up a consumable from the backpack and returns the health it restored.
+    pub fn use_item(&mut self, item_name: &str) -> Result<i128, String> {
+        match self.backpack.take_item(item_name) {
+            Some(item) if item.is_consumable() => Ok(self.heal(item.heal_amount())),
+            Some(item) if item.teaches.is_some() => {
+                let err = format!("{} has to be read outside of battle.", item.name);
+                self.backpack.add_item(item);
+                Err(err)
+            }
+            Some(item) => {
+                let err = format!("{} can't be used up.", item.name);
+                self.backpack.add_item(item);
+                Err(err)
+            }
+            None => Err("Item not found in the backpack.".to_string()),
         }
     }
 
-    pub fn equip_item(&mut self, item_name: &str) -> Result<(), String> {
-        return self.backpack.equip_item(item_name);
+    /// Wears down the gear involved in a hit, weapons for the one who dealt it
+    /// and armour for the one who took it, and returns the names of anything
+    /// that broke.
+    pub fn wear_gear(&mut self, dealt_hit: bool) -> Vec<String> {
+        let broken = if dealt_hit {
+            self.backpack.wear_weapons()
+        } else {
+            self.backpack.wear_armour().into_iter().collect()
+        };
+        if !broken.is_empty() {
+            self.refresh_stats();
+        }
+        broken
+    }
+
+    /// Uses up a recipe's materials and gold to make its item, returning the item's name.
+    pub fn craft(&mut self, recipe: &Recipe) -> Result<String, String> {
+        if self.level < recipe.level {
+            return Err(format!("{} is learned at level {}.", recipe.name, recipe.level));
+        }
+        let missing = recipe.missing(&self.backpack);
+        if !missing.is_empty() {
+            return Err(format!("{} still needs {}.", recipe.name, format_materials(&missing)));
+        }
+        if self.gold < recipe.gold {
+            return Err(format!("{} costs {} gold but you only have {}.", recipe.name, recipe.gold, self.gold));
+        }
+        let item = recipe.craft_item();
+        // Items are kept by name, so a second copy would replace the first
+        if !item.is_stackable() && self.backpack.find_item(&item.name).is_some() {
+            return Err(format!("You already have a {}.", item.name));
+        }
+        self.backpack.remove_materials(recipe.materials);
+        self.gold -= recipe.gold;
+        let name = item.name.clone();
+        self.backpack.add_item(item);
+        Ok(name)
+    }
+
+    /// Spends materials and gold on trying to raise an item's upgrade level.
+    /// Returns whether the attempt worked, the cost is paid either way.
+    pub fn upgrade_item(&mut self, item_name: &str) -> Result<bool, String> {
+        let item = self.backpack.find_item_mut(item_name).ok_or("Item not found in the backpack.")?;
+        if !item.is_gear() {
+            return Err(format!("{} can't be upgraded.", item.name));
+        }
+        if item.upgrade_level >= MAX_UPGRADE_LEVEL {
+            return Err(format!("{} is already at +{}.", item.name, MAX_UPGRADE_LEVEL));
+        }
+        let (materials, gold) = upgrade_cost(item);
+        if self.gold < gold {
+            return Err(format!("Upgrading {} costs {} gold but you only have {}.", item_name, gold, self.gold));
+        }
+        if !self.backpack.remove_materials(&materials) {
+            return Err(format!("Upgrading {} takes {}.", item_name, format_materials(&materials)));
+        }
+        self.gold -= gold;
+        let item = self.backpack.find_item_mut(item_name).ok_or("Item not found in the backpack.")?;
+        if !roll_upgrade(item) {
+            return Ok(false);
+        }
+        item.upgrade();
+        self.refresh_stats();
+        Ok(true)
+    }
+
+    /// Adds a random enchantment to an item, replacing the one it had.
+    pub fn enchant_item(&mut self, item_name: &str) -> Result<Enchantment, String> {
+        let item = self.backpack.find_item_mut(item_name).ok_or("Item not found in the backpack.")?;
+        if !item.is_gear() {
+            return Err(format!("{} can't be enchanted.", item.name));
+        }
+        if self.gold < ENCHANT_GOLD {
+            return Err(format!("Enchanting costs {} gold but you only have {}.", ENCHANT_GOLD, self.gold));
+        }
+        if !self.backpack.remove_materials(&ENCHANT_MATERIALS) {
+            return Err(format!("Enchanting takes {}.", format_materials(&ENCHANT_MATERIALS)));
+        }
+        self.gold -= ENCHANT_GOLD;
+        let item = self.backpack.find_item_mut(item_name).ok_or("Item not found in the backpack.")?;
+        let enchantment = random_enchantment(item.slot);
+        item.enchantment = Some(enchantment);
+        self.refresh_stats();
+        Ok(enchantment)
+    }
+
+    pub fn socket_gem(&mut self, gem_name: &str, item_name: &str) -> Result<(), String> {
+        self.backpack.socket_gem(gem_name, item_name)?;
+        self.refresh_stats();
+        Ok(())
+    }
+
+    pub fn unsocket_gem(&mut self, item_name: &str, socket: usize) -> Result<String, String> {
+        let gem = self.backpack.unsocket_gem(item_name, socket)?;
+        self.refresh_stats();
+        Ok(gem)
+    }
+
+    /// Pays the blacksmith to fully repair an item and returns the gold spent.
+    pub fn repair_item(&mut self, item_name: &str) -> Result<i128, String> {
+        let gold = self.gold;
+        let item = self.backpack.find_item_mut(item_name).ok_or("Item not found in the backpack.")?;
+        let cost = item.repair_cost();
+        if cost <= 0 {
+            return Err(format!("{} doesn't need repairing.", item.name));
+        }
+        if gold < cost {
+            return Err(format!("Repairing {} costs {} gold but you only have {}.", item.name, cost, gold));
+        }
+        item.repair(item.missing_durability());
+        self.gold -= cost;
+        self.refresh_stats();
+        Ok(cost)
+    }
+
+    /// Uses up a repair kit on an item and returns the durability restored.
+    pub fn use_repair_kit(&mut self, kit_name: &str, item_name: &str) -> Result<i128, String> {
+        let needs_repair = self
+            .backpack
+            .find_item_mut(item_name)
+            .map(|item| item.missing_durability() > 0)
+            .ok_or("Item not found in the backpack.")?;
+        if !needs_repair {
+            return Err(format!("{} doesn't need repairing.", item_name));
+        }
+        let kit = match self.backpack.take_item(kit_name) {
+            Some(kit) if kit.is_repair_kit() => kit,
+            Some(item) => {
+                let err = format!("{} is not a repair kit.", item.name);
+                self.backpack.add_item(item);
+                return Err(err);
+            }
+            None => return Err("Repair kit not found in the backpack.".to_string()),
+        };
+        let restored = self
+            .backpack
+            .find_item_mut(item_name)
+            .map_or(0, |item| item.repair(kit.repair_amount));
+        self.refresh_stats();
+        Ok(restored)
+    }
+
+    /// Reads a scroll from the backpack, using it up, and returns the ability it taught.
+    pub fn read_scroll(&mut self, item_name: &str) -> Result<String, String> {
+        let Some(item) = self.backpack.take_item(item_name) else {
+            return Err("Item not found in the backpack.".to_string());
+        };
+        let Some(ability) = item.teaches.as_deref().and_then(find_ability) else {
+            let err = format!("{} is not a scroll.", item.name);
+            self.backpack.add_item(item);
+            return Err(err);
+        };
+        if !self.learn_ability(ability.clone()) {
+            let err = format!("You already know {}.", ability.name);
+            self.backpack.add_item(item);
+            return Err(err);
+        }
+        Ok(ability.name)
+    }
+
+    /// Equips an item from the backpack once its requirements are met.
+    pub fn equip_item(&mut self, item_name: &str) -> Result<(), EquipError> {
+        let item = self.backpack.items.get(item_name).ok_or(EquipError::NotFound)?;
+        item.requirements.check(self)?;
+        let dual_wield = self.class.is_some_and(|class| class.can_dual_wield());
+        self.backpack.equip_item(item_name, dual_wield)
     }
 
     pub fn unequip_item(&mut self, slot: &Slot) -> Option<Item> {
@@ -110,10 +797,16 @@ impl Entity {
 
 impl fmt::Display for Entity {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.hardcore {
+            write!(f, "[HARDCORE] ")?;
+        }
+        if let Some(class) = self.class {
+            write!(f, "[{}] ", class)?;
+        }
         write!(
             f,
-            "Name: {}, Age: {}, Health: {}, Damage: {}, Level: {}, XP Stored: {}, XP Dropped: {}",
-            self.name, self.age, self.health, self.damage, self.level, self.xp_stored, self.xp_drop
+            "Name: {}, Age: {}, Health: {}/{}, Mana: {}/{}, Damage: {}, Speed: {}, Level: {}, XP Stored: {}, XP Dropped: {}, Gold: {}",
+            self.name, self.age, self.current_health, self.max_health, self.current_mana, self.max_mana, self.damage, self.get_speed(), self.level, self.xp_stored, self.xp_drop, self.gold
         )
     }
 }
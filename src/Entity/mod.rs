@@ -1 +1,6 @@
-pub mod entity;
\ No newline at end of file
+pub mod ability;
+pub mod boss;
+pub mod entity;
+pub mod skill_tree;
+pub mod status;
+pub mod template;
\ No newline at end of file
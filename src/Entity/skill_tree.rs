@@ -0,0 +1,201 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use crate::backpack::backpack::Modifiers;
+use crate::damage::{DamageType, Resistances};
+
+/// Picked at character creation, decides which skill tree the player grows along.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Class {
+    Warrior,
+    Mage,
+    Rogue,
+}
+
+impl Class {
+    pub const ALL: [Class; 3] = [Class::Warrior, Class::Mage, Class::Rogue];
+
+    /// Whether the class can hold a one-handed weapon in each hand.
+    pub fn can_dual_wield(&self) -> bool {
+        matches!(self, Class::Rogue)
+    }
+}
+
+impl fmt::Display for Class {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let class_str = match self {
+            Class::Warrior => "Warrior",
+            Class::Mage => "Mage",
+            Class::Rogue => "Rogue",
+        };
+        write!(f, "{}", class_str)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum TalentBonus {
+    Damage(i128),
+    MaxHealth(i128),
+    MaxMana(i128),
+    Modifiers(Modifiers),
+    Resistance(DamageType, i128),
+    /// Name of an ability from the catalog.
+    Ability(&'static str),
+}
+
+impl fmt::Display for TalentBonus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TalentBonus::Damage(amount) => write!(f, "{:+} Damage", amount),
+            TalentBonus::MaxHealth(amount) => write!(f, "{:+} Max Health", amount),
+            TalentBonus::MaxMana(amount) => write!(f, "{:+} Max Mana", amount),
+            TalentBonus::Modifiers(modifiers) => write!(f, "{}", modifiers),
+            TalentBonus::Resistance(damage_type, percent) => write!(f, "{:+}% {} Resist", percent, damage_type),
+            TalentBonus::Ability(name) => write!(f, "Unlocks {}", name),
+        }
+    }
+}
+
+/// A node in a class skill tree, unlocked once with talent points.
+#[derive(Debug, Clone)]
+pub struct Talent {
+    pub id: &'static str,
+    pub name: &'static str,
+    pub cost: i128,
+    /// Ids of talents that must be unlocked first.
+    pub requires: Vec<&'static str>,
+    pub bonuses: Vec<TalentBonus>,
+}
+
+impl fmt::Display for Talent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let bonuses: Vec<String> = self.bonuses.iter().map(|bonus| bonus.to_string()).collect();
+        write!(f, "{} ({} pt): {}", self.name, self.cost, bonuses.join(", "))
+    }
+}
+
+/// Sum of the passive bonuses from a set of unlocked talents.
+#[derive(Debug, Clone, Default)]
+pub struct TalentTotals {
+    pub damage: i128,
+    pub max_health: i128,
+    pub max_mana: i128,
+    pub modifiers: Modifiers,
+    pub resistances: Resistances,
+    pub abilities: Vec<&'static str>,
+}
+
+fn talent(id: &'static str, name: &'static str, cost: i128, requires: &[&'static str], bonuses: Vec<TalentBonus>) -> Talent {
+    Talent {
+        id,
+        name,
+        cost,
+        requires: requires.to_vec(),
+        bonuses,
+    }
+}
+
+pub fn skill_tree(class: Class) -> Vec<Talent> {
+    match class {
+        Class::Warrior => vec![
+            talent("toughness", "Toughness", 1, &[], vec![TalentBonus::MaxHealth(20)]),
+            talent("weapon_mastery", "Weapon Mastery", 1, &[], vec![TalentBonus::Damage(3)]),
+            talent("iron_skin", "Iron Skin", 1, &["toughness"], vec![TalentBonus::Resistance(DamageType::Physical, 15)]),
+            talent(
+                "berserker",
+                "Berserker",
+                2,
+                &["weapon_mastery"],
+                vec![TalentBonus::Modifiers(Modifiers {
+                    crit_chance: 10,
+                    crit_multiplier: 25,
+                    ..Modifiers::default()
+                })],
+            ),
+            talent("shield_bash", "Shield Bash", 2, &["iron_skin", "weapon_mastery"], vec![TalentBonus::Ability("Shield Bash")]),
+        ],
+        Class::Mage => vec![
+            talent("arcane_mind", "Arcane Mind", 1, &[], vec![TalentBonus::MaxMana(15)]),
+            talent(
+                "focus",
+                "Focus",
+                1,
+                &[],
+                vec![TalentBonus::Modifiers(Modifiers {
+                    hit_chance: 5,
+                    ..Modifiers::default()
+                })],
+            ),
+            talent("spell_power", "Spell Power", 1, &["arcane_mind"], vec![TalentBonus::Damage(4)]),
+            talent(
+                "elemental_ward",
+                "Elemental Ward",
+                1,
+                &["arcane_mind"],
+                vec![
+                    TalentBonus::Resistance(DamageType::Fire, 15),
+                    TalentBonus::Resistance(DamageType::Ice, 15),
+                    TalentBonus::Resistance(DamageType::Lightning, 15),
+                ],
+            ),
+            talent("arcane_surge", "Arcane Surge", 2, &["focus", "spell_power"], vec![TalentBonus::Ability("Arcane Surge")]),
+        ],
+        Class::Rogue => vec![
+            talent(
+                "agility",
+                "Agility",
+                1,
+                &[],
+                vec![TalentBonus::Modifiers(Modifiers {
+                    speed: 3,
+                    ..Modifiers::default()
+                })],
+            ),
+            talent(
+                "precision",
+                "Precision",
+                1,
+                &[],
+                vec![TalentBonus::Modifiers(Modifiers {
+                    crit_chance: 8,
+                    ..Modifiers::default()
+                })],
+            ),
+            talent(
+                "dodge",
+                "Dodge",
+                1,
+                &["agility"],
+                vec![TalentBonus::Modifiers(Modifiers {
+                    evasion: 8,
+                    ..Modifiers::default()
+                })],
+            ),
+            talent("venom", "Venom", 1, &["precision"], vec![TalentBonus::Ability("Envenom")]),
+            talent("backstab", "Backstab", 2, &["agility", "precision"], vec![TalentBonus::Ability("Backstab")]),
+        ],
+    }
+}
+
+pub fn find_talent(class: Class, id: &str) -> Option<Talent> {
+    skill_tree(class).into_iter().find(|talent| talent.id == id)
+}
+
+pub fn talent_totals(class: Option<Class>, unlocked: &[String]) -> TalentTotals {
+    let mut totals = TalentTotals::default();
+    let Some(class) = class else {
+        return totals;
+    };
+    for talent in skill_tree(class).iter().filter(|talent| unlocked.iter().any(|id| id == talent.id)) {
+        for bonus in &talent.bonuses {
+            match bonus {
+                TalentBonus::Damage(amount) => totals.damage += amount,
+                TalentBonus::MaxHealth(amount) => totals.max_health += amount,
+                TalentBonus::MaxMana(amount) => totals.max_mana += amount,
+                TalentBonus::Modifiers(modifiers) => totals.modifiers = totals.modifiers + *modifiers,
+                TalentBonus::Resistance(damage_type, percent) => totals.resistances.add(*damage_type, *percent),
+                TalentBonus::Ability(name) => totals.abilities.push(name),
+            }
+        }
+    }
+    totals
+}
@@ -0,0 +1,162 @@
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use crate::damage::DamageType;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum StatusKind {
+    Poison,
+    Bleed,
+    Stun,
+    Burn,
+    Regen,
+}
+
+/// What happens when an effect is applied to an entity that already has it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Stacking {
+    /// Adds a stack up to the limit and refreshes the duration.
+    Intensify { max_stacks: i128 },
+    /// Keeps a single stack, taking the stronger potency and longer duration.
+    Refresh,
+    /// Adds the new duration on top of what is left.
+    Extend,
+    /// Has no effect until the current one wears off.
+    Ignore,
+}
+
+impl StatusKind {
+    pub fn stacking(&self) -> Stacking {
+        match self {
+            StatusKind::Poison => Stacking::Intensify { max_stacks: 5 },
+            StatusKind::Bleed => Stacking::Intensify { max_stacks: 3 },
+            StatusKind::Burn => Stacking::Refresh,
+            StatusKind::Regen => Stacking::Extend,
+            // Re-stunning every turn would lock a target down for good
+            StatusKind::Stun => Stacking::Ignore,
+        }
+    }
+
+    /// The resistance that softens this effect's damage. Bleeding ignores armour.
+    pub fn damage_type(&self) -> Option<DamageType> {
+        match self {
+            StatusKind::Poison => Some(DamageType::Poison),
+            StatusKind::Burn => Some(DamageType::Fire),
+            StatusKind::Bleed | StatusKind::Stun | StatusKind::Regen => None,
+        }
+    }
+}
+
+impl fmt::Display for StatusKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let kind_str = match self {
+            StatusKind::Poison => "Poison",
+            StatusKind::Bleed => "Bleed",
+            StatusKind::Stun => "Stun",
+            StatusKind::Burn => "Burn",
+            StatusKind::Regen => "Regen",
+        };
+        write!(f, "{}", kind_str)
+    }
+}
+
+/// An effect on an entity that ticks at the start of every battle round.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct StatusEffect {
+    pub kind: StatusKind,
+    /// Rounds left before the effect wears off.
+    pub duration: i128,
+    /// Damage or healing per stack each round. Unused by stun.
+    pub potency: i128,
+    pub stacks: i128,
+}
+
+impl StatusEffect {
+    pub fn new(kind: StatusKind, duration: i128, potency: i128) -> Self {
+        StatusEffect {
+            kind,
+            duration,
+            potency,
+            stacks: 1,
+        }
+    }
+
+    /// Folds a newly applied effect of the same kind into this one.
+    pub fn stack(&mut self, other: &StatusEffect) {
+        match self.kind.stacking() {
+            Stacking::Intensify { max_stacks } => {
+                self.stacks = (self.stacks + other.stacks).min(max_stacks);
+                self.duration = self.duration.max(other.duration);
+                self.potency = self.potency.max(other.potency);
+            }
+            Stacking::Refresh => {
+                self.duration = self.duration.max(other.duration);
+                self.potency = self.potency.max(other.potency);
+            }
+            Stacking::Extend => self.duration += other.duration,
+            Stacking::Ignore => {}
+        }
+    }
+
+    /// Health lost this tick, negative for healing.
+    pub fn health_change(&self) -> i128 {
+        match self.kind {
+            StatusKind::Poison | StatusKind::Bleed | StatusKind::Burn => self.potency * self.stacks,
+            StatusKind::Regen => -(self.potency * self.stacks),
+            StatusKind::Stun => 0,
+        }
+    }
+}
+
+impl fmt::Display for StatusEffect {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.kind)?;
+        if self.stacks > 1 {
+            write!(f, " x{}", self.stacks)?;
+        }
+        write!(f, " ({} {})", self.duration, if self.duration == 1 { "round" } else { "rounds" })
+    }
+}
+
+/// A status effect inflicted on the target of a successful hit, some of the time.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct OnHitEffect {
+    pub effect: StatusEffect,
+    /// Chance in percent of applying the effect.
+    pub chance: i128,
+}
+
+impl OnHitEffect {
+    pub fn new(kind: StatusKind, duration: i128, potency: i128, chance: i128) -> Self {
+        OnHitEffect {
+            effect: StatusEffect::new(kind, duration, potency),
+            chance,
+        }
+    }
+
+    pub fn roll(&self) -> Option<StatusEffect> {
+        if rand::thread_rng().gen_range(0..100) < self.chance {
+            Some(self.effect)
+        } else {
+            None
+        }
+    }
+}
+
+impl fmt::Display for OnHitEffect {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}% chance to {} for {} rounds",
+            self.chance, self.effect.kind, self.effect.duration
+        )
+    }
+}
+
+/// What a status effect did when it ticked.
+#[derive(Debug, Clone)]
+pub struct StatusTick {
+    pub kind: StatusKind,
+    pub health_change: i128,
+    pub expired: bool,
+}
@@ -0,0 +1,121 @@
+use rand::Rng;
+use std::ops::Range;
+use crate::ai::AiKind;
+use crate::backpack::crafting::Material;
+use crate::damage::{DamageType, Resistances};
+use crate::status::{OnHitEffect, StatusKind};
+
+/// The kind of monster an enemy is drawn from, with its stat ranges and behaviour.
+#[derive(Debug, Clone)]
+pub struct EnemyTemplate {
+    pub kind: &'static str,
+    pub health: Range<i128>,
+    pub damage: Range<i128>,
+    pub xp_drop: Range<i128>,
+    pub gold: Range<i128>,
+    pub speed: Range<i128>,
+    pub attack_type: DamageType,
+    /// Negative values are weaknesses.
+    pub resistances: Resistances,
+    pub ai: AiKind,
+    pub on_hit: Option<OnHitEffect>,
+    /// Names of abilities from the catalog this enemy knows.
+    pub abilities: Vec<&'static str>,
+    /// Extra potions carried on top of the usual random item.
+    pub potions: usize,
+    /// Crafting materials dropped on defeat, each rolled from its range.
+    pub materials: Vec<(Material, Range<i128>)>,
+}
+
+pub fn enemy_templates() -> Vec<EnemyTemplate> {
+    vec![
+        EnemyTemplate {
+            kind: "Goblin",
+            health: 10..40,
+            damage: 2..6,
+            xp_drop: 5..25,
+            gold: 5..25,
+            speed: 10..18,
+            attack_type: DamageType::Physical,
+            resistances: Resistances::new(&[(DamageType::Poison, 25), (DamageType::Fire, -25)]),
+            ai: AiKind::Coward,
+            on_hit: Some(OnHitEffect::new(StatusKind::Bleed, 3, 2, 30)),
+            abilities: vec![],
+            potions: 0,
+            materials: vec![(Material::Leather, 0..3), (Material::ScrapMetal, 0..2)],
+        },
+        EnemyTemplate {
+            kind: "Orc",
+            health: 40..100,
+            damage: 4..10,
+            xp_drop: 20..50,
+            gold: 0..15,
+            speed: 4..10,
+            attack_type: DamageType::Physical,
+            resistances: Resistances::new(&[(DamageType::Physical, 10), (DamageType::Lightning, -25)]),
+            ai: AiKind::Aggressive,
+            on_hit: Some(OnHitEffect::new(StatusKind::Stun, 1, 0, 15)),
+            abilities: vec!["Power Strike"],
+            potions: 0,
+            materials: vec![(Material::ScrapMetal, 1..4), (Material::Leather, 0..2)],
+        },
+        EnemyTemplate {
+            kind: "Skeleton",
+            health: 20..70,
+            damage: 3..8,
+            xp_drop: 10..40,
+            gold: 0..10,
+            speed: 6..12,
+            attack_type: DamageType::Physical,
+            resistances: Resistances::new(&[(DamageType::Physical, 20), (DamageType::Poison, 100), (DamageType::Holy, -50)]),
+            ai: AiKind::Defensive,
+            on_hit: None,
+            abilities: vec![],
+            potions: 0,
+            materials: vec![(Material::Bone, 1..4), (Material::ScrapMetal, 0..2)],
+        },
+        EnemyTemplate {
+            kind: "Shaman",
+            health: 20..50,
+            damage: 2..7,
+            xp_drop: 15..45,
+            gold: 5..20,
+            speed: 6..12,
+            attack_type: DamageType::Poison,
+            resistances: Resistances::new(&[(DamageType::Poison, 50), (DamageType::Shadow, 25), (DamageType::Lightning, -25)]),
+            ai: AiKind::Healer,
+            on_hit: Some(OnHitEffect::new(StatusKind::Poison, 3, 1, 35)),
+            abilities: vec!["Mend", "Envenom"],
+            potions: 2,
+            materials: vec![(Material::Herbs, 1..4), (Material::ArcaneDust, 0..2)],
+        },
+        EnemyTemplate {
+            kind: "Imp",
+            health: 10..35,
+            damage: 3..9,
+            xp_drop: 5..30,
+            gold: 0..20,
+            speed: 12..20,
+            attack_type: DamageType::Fire,
+            resistances: Resistances::new(&[(DamageType::Fire, 75), (DamageType::Ice, -50), (DamageType::Holy, -25)]),
+            ai: AiKind::Random,
+            on_hit: Some(OnHitEffect::new(StatusKind::Burn, 2, 3, 25)),
+            abilities: vec!["Fireball"],
+            potions: 1,
+            materials: vec![(Material::EmberEssence, 1..3), (Material::ArcaneDust, 0..2)],
+        },
+    ]
+}
+
+pub fn random_enemy_template() -> EnemyTemplate {
+    let mut templates = enemy_templates();
+    let index = rand::thread_rng().gen_range(0..templates.len());
+    templates.swap_remove(index)
+}
+
+/// How many enemies a fight against the given player level throws at them,
+/// growing by one every few levels up to a group of three.
+pub fn encounter_size(level: i128) -> usize {
+    let max = (1 + level / 3).clamp(1, 3) as usize;
+    rand::thread_rng().gen_range(1..=max)
+}
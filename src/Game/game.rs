@@ -0,0 +1,159 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use crate::entity::Entity;
+
+/// What losing a battle costs the player.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum DeathPolicy {
+    /// Lose a share of stored xp and gold, then wake up at the inn.
+    Penalty {
+        xp_loss_percent: i128,
+        gold_loss_percent: i128,
+    },
+    /// The run ends and the save file is deleted.
+    Permadeath,
+}
+
+impl Default for DeathPolicy {
+    fn default() -> Self {
+        DeathPolicy::Penalty {
+            xp_loss_percent: 25,
+            gold_loss_percent: 50,
+        }
+    }
+}
+
+/// Chosen at character creation and fixed for the whole run.
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub enum GameMode {
+    #[default]
+    Normal,
+    /// One life: death always ends the run and deletes the save.
+    Hardcore,
+}
+
+impl fmt::Display for GameMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mode_str = match self {
+            GameMode::Normal => "Normal",
+            GameMode::Hardcore => "Hardcore",
+        };
+        write!(f, "{}", mode_str)
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct GameSettings {
+    #[serde(default)]
+    pub mode: GameMode,
+    pub death_policy: DeathPolicy,
+}
+
+impl GameSettings {
+    /// Reads settings from the command line, e.g. `--permadeath`.
+    pub fn from_args() -> Self {
+        let mut settings = GameSettings::default();
+        for arg in std::env::args().skip(1) {
+            if arg == "--permadeath" {
+                settings.death_policy = DeathPolicy::Permadeath;
+            }
+        }
+        settings
+    }
+
+    pub fn set_mode(&mut self, mode: GameMode) {
+        self.mode = mode;
+        if mode == GameMode::Hardcore {
+            self.death_policy = DeathPolicy::Permadeath;
+        }
+    }
+}
+
+/// Running totals for the current character, shown on the game over screen.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RunStats {
+    pub turns: i128,
+    pub battles_won: i128,
+    pub battles_lost: i128,
+    #[serde(default)]
+    pub battles_fled: i128,
+    pub deaths: i128,
+    pub xp_earned: i128,
+    pub gold_earned: i128,
+    pub highest_level: i128,
+    #[serde(default)]
+    pub bosses_defeated: i128,
+}
+
+impl RunStats {
+    pub fn record_level(&mut self, level: i128) {
+        self.highest_level = self.highest_level.max(level);
+    }
+}
+
+impl fmt::Display for RunStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Turns Played: {}\n\
+             Battles Won: {}\n\
+             Battles Lost: {}\n\
+             Battles Fled: {}\n\
+             Deaths: {}\n\
+             XP Earned: {}\n\
+             Gold Earned: {}\n\
+             Highest Level: {}\n\
+             Bosses Defeated: {}",
+            self.turns,
+            self.battles_won,
+            self.battles_lost,
+            self.battles_fled,
+            self.deaths,
+            self.xp_earned,
+            self.gold_earned,
+            self.highest_level,
+            self.bosses_defeated
+        )
+    }
+}
+
+/// What happened to the player after losing a battle.
+#[derive(Debug, Clone, PartialEq)]
+pub enum DefeatOutcome {
+    Respawned { xp_lost: i128, gold_lost: i128 },
+    GameOver,
+}
+
+/// Applies the configured death policy to a defeated player.
+pub fn apply_defeat(player: &mut Entity, settings: &GameSettings, stats: &mut RunStats) -> DefeatOutcome {
+    stats.battles_lost += 1;
+    stats.deaths += 1;
+
+    match settings.death_policy {
+        DeathPolicy::Penalty {
+            xp_loss_percent,
+            gold_loss_percent,
+        } => {
+            let xp_lost = player.xp_stored * xp_loss_percent / 100;
+            let gold_lost = player.gold * gold_loss_percent / 100;
+            player.xp_stored -= xp_lost;
+            player.gold -= gold_lost;
+            // The inn is the safe point players wake up at
+            player.restore();
+            DefeatOutcome::Respawned { xp_lost, gold_lost }
+        }
+        DeathPolicy::Permadeath => DefeatOutcome::GameOver,
+    }
+}
+
+pub fn print_game_over(player: &Entity, settings: &GameSettings, stats: &RunStats) {
+    println!("==============================");
+    println!("          GAME OVER");
+    println!("==============================");
+    println!("{} has fallen at level {}.", player.name, player.level);
+    if settings.mode == GameMode::Hardcore {
+        println!("Mode: {}", GameMode::Hardcore);
+    }
+    println!("{}", stats);
+    println!("==============================");
+}
@@ -0,0 +1,90 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::fs;
+use std::io;
+use crate::entity::Entity;
+use crate::game::{GameMode, RunStats};
+
+const LEADERBOARD_PATH: &str = "leaderboard.json";
+const HARDCORE_LEADERBOARD_PATH: &str = "leaderboard_hardcore.json";
+const MAX_ENTRIES: usize = 10;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LeaderboardEntry {
+    pub name: String,
+    pub level: i128,
+    pub battles_won: i128,
+    pub xp_earned: i128,
+    pub gold_earned: i128,
+}
+
+impl fmt::Display for LeaderboardEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} - Level: {}, Battles Won: {}, XP Earned: {}, Gold Earned: {}",
+            self.name, self.level, self.battles_won, self.xp_earned, self.gold_earned
+        )
+    }
+}
+
+/// Best finished runs, kept in a separate file per game mode.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Leaderboard {
+    pub entries: Vec<LeaderboardEntry>,
+}
+
+impl Leaderboard {
+    fn path(mode: GameMode) -> &'static str {
+        match mode {
+            GameMode::Normal => LEADERBOARD_PATH,
+            GameMode::Hardcore => HARDCORE_LEADERBOARD_PATH,
+        }
+    }
+
+    pub fn load(mode: GameMode) -> Self {
+        fs::read_to_string(Self::path(mode))
+            .ok()
+            .and_then(|contents| serde_json::from_str(&contents).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn write(&self, mode: GameMode) -> io::Result<()> {
+        let contents = serde_json::to_string_pretty(self).map_err(io::Error::other)?;
+        fs::write(Self::path(mode), contents)
+    }
+
+    /// Adds a finished run and returns its rank, or `None` if it didn't make the board.
+    pub fn record(&mut self, player: &Entity, stats: &RunStats) -> Option<usize> {
+        let entry = LeaderboardEntry {
+            name: player.name.clone(),
+            level: stats.highest_level.max(player.level),
+            battles_won: stats.battles_won,
+            xp_earned: stats.xp_earned,
+            gold_earned: stats.gold_earned,
+        };
+        // A run recorded before, on an earlier quit or defeat, only keeps its latest entry
+        self.entries.retain(|other| other.name != entry.name);
+        let rank = self
+            .entries
+            .iter()
+            .position(|other| (entry.level, entry.xp_earned) > (other.level, other.xp_earned))
+            .unwrap_or(self.entries.len());
+        if rank >= MAX_ENTRIES {
+            return None;
+        }
+        self.entries.insert(rank, entry);
+        self.entries.truncate(MAX_ENTRIES);
+        Some(rank + 1)
+    }
+
+    pub fn print(&self, mode: GameMode) {
+        println!("{} Leaderboard:", mode);
+        if self.entries.is_empty() {
+            println!("    No runs recorded yet.");
+        }
+        for (count, entry) in self.entries.iter().enumerate() {
+            println!("    {}: {}", count + 1, entry);
+        }
+    }
+}
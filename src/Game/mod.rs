@@ -0,0 +1,4 @@
+pub mod game;
+pub mod leaderboard;
+pub mod party;
+pub mod save;
\ No newline at end of file
@@ -0,0 +1,117 @@
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use crate::ai::AiKind;
+use crate::entity::Entity;
+use crate::skill_tree::Class;
+
+/// How many companions can travel with the player at once.
+pub const MAX_COMPANIONS: usize = 3;
+/// Gold a hireling asks for each of their levels.
+const HIRE_COST_PER_LEVEL: i128 = 40;
+/// Chance in percent that a beaten enemy begs to join instead of dying.
+const SPARE_CHANCE: i128 = 20;
+
+/// The companions fighting alongside the player. The player is not a member.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Party {
+    pub companions: Vec<Entity>,
+}
+
+impl Party {
+    pub fn is_full(&self) -> bool {
+        self.companions.len() >= MAX_COMPANIONS
+    }
+
+    pub fn recruit(&mut self, companion: Entity) -> Result<(), String> {
+        if self.is_full() {
+            return Err(format!("Your party can't hold more than {} companions.", MAX_COMPANIONS));
+        }
+        println!("{} joins your party", companion.name);
+        self.companions.push(companion);
+        Ok(())
+    }
+
+    /// Pays for a hireling and adds them to the party.
+    pub fn hire(&mut self, player: &mut Entity, hireling: Entity) -> Result<(), String> {
+        let cost = hire_cost(&hireling);
+        if player.gold < cost {
+            return Err(format!("{} asks for {} gold but you only have {}.", hireling.name, cost, player.gold));
+        }
+        self.recruit(hireling)?;
+        player.gold -= cost;
+        Ok(())
+    }
+
+    /// Takes in a beaten enemy, healed up and with their grudges forgotten.
+    pub fn spare(&mut self, mut enemy: Entity) -> Result<(), String> {
+        enemy.restore();
+        enemy.gold = 0;
+        if enemy.ai == AiKind::Coward {
+            enemy.ai = AiKind::Aggressive;
+        }
+        self.recruit(enemy)
+    }
+
+    /// Removes a companion, returning them so their gear can be handed back.
+    pub fn dismiss(&mut self, index: usize) -> Result<Entity, String> {
+        if index >= self.companions.len() {
+            return Err("No companion with that number.".to_string());
+        }
+        Ok(self.companions.remove(index))
+    }
+
+    pub fn rename(&mut self, index: usize, name: &str) -> Result<(), String> {
+        let name = name.trim();
+        if name.is_empty() {
+            return Err("A companion needs a name.".to_string());
+        }
+        let companion = self.companions.get_mut(index).ok_or("No companion with that number.")?;
+        companion.name = name.to_string();
+        Ok(())
+    }
+
+    /// Companions still on their feet and able to join a fight.
+    pub fn ready(&mut self) -> Vec<&mut Entity> {
+        self.companions.iter_mut().filter(|companion| companion.is_alive()).collect()
+    }
+
+    pub fn restore(&mut self) {
+        for companion in &mut self.companions {
+            companion.restore();
+        }
+    }
+
+    /// Out-of-combat regeneration for every companion, the same as the player gets.
+    pub fn regenerate(&mut self) {
+        for companion in &mut self.companions {
+            companion.regenerate();
+            companion.regenerate_mana();
+        }
+    }
+}
+
+/// A random adventurer of a random class, trained up to `level`.
+pub fn new_hireling(level: i128) -> Entity {
+    let mut hireling = Entity::new();
+    let class = Class::ALL[rand::thread_rng().gen_range(0..Class::ALL.len())];
+    hireling.class = Some(class);
+    hireling.ai = match class {
+        Class::Warrior | Class::Rogue => AiKind::Aggressive,
+        Class::Mage => AiKind::Healer,
+    };
+    while hireling.level < level {
+        hireling.add_xp(hireling.xp_threshold - hireling.xp_stored);
+    }
+    hireling.gold = 0;
+    hireling.restore();
+    hireling
+}
+
+pub fn hire_cost(hireling: &Entity) -> i128 {
+    hireling.level * HIRE_COST_PER_LEVEL
+}
+
+/// Rolls whether a beaten enemy offers to join rather than fall.
+pub fn roll_spare() -> bool {
+    rand::thread_rng().gen_range(0..100) < SPARE_CHANCE
+}
@@ -0,0 +1,42 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use crate::entity::Entity;
+use crate::game::{GameSettings, RunStats};
+use crate::party::Party;
+
+pub const SAVE_PATH: &str = "savegame.json";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SaveGame {
+    pub player: Entity,
+    pub settings: GameSettings,
+    pub stats: RunStats,
+    #[serde(default)]
+    pub party: Party,
+}
+
+impl SaveGame {
+    pub fn load() -> Option<SaveGame> {
+        let contents = fs::read_to_string(SAVE_PATH).ok()?;
+        match serde_json::from_str(&contents) {
+            Ok(save) => Some(save),
+            Err(err) => {
+                println!("Could not read save file: {}", err);
+                None
+            }
+        }
+    }
+
+    pub fn write(&self) -> io::Result<()> {
+        let contents = serde_json::to_string_pretty(self).map_err(io::Error::other)?;
+        fs::write(SAVE_PATH, contents)
+    }
+
+    pub fn delete() -> io::Result<()> {
+        match fs::remove_file(SAVE_PATH) {
+            Err(err) if err.kind() != io::ErrorKind::NotFound => Err(err),
+            _ => Ok(()),
+        }
+    }
+}
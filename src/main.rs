@@ -1,56 +1,816 @@
 use std::io::{stdout, Write};
-use termion::clear;
+use termion::{clear, color};
 
-use backpack::backpack::Slot;
+use backpack::backpack::{Rarity, Slot};
+use backpack::crafting;
+use backpack::enchanting;
 // main.rs
 use text_io::read;
+use Battle::ai;
 use Battle::battle;
+use Battle::damage;
+use Entity::ability;
+use Entity::boss;
 use Entity::entity;
+use Entity::skill_tree;
+use Entity::status;
+use Entity::template;
+use Game::game;
+use Game::leaderboard;
+use Game::party;
+use Game::save;
 mod backpack;
+#[allow(non_snake_case)]
 mod Battle;
+#[allow(non_snake_case)]
 mod Entity;
+#[allow(non_snake_case)]
+mod Game;
 
 
 fn main() {
-    let mut is_first_time: bool = true;
-    let mut attacker: entity::Entity = entity::Entity::new();
-    let mut defender: entity::Entity;
-
-    loop {
-        if is_first_time {
+    let (mut attacker, mut party, settings, mut stats) = match save::SaveGame::load() {
+        Some(save) => {
+            println!("Welcome back to Dungeon Fighter. Your save has been loaded");
+            let mut player = save.player;
+            // The settings decide the mode, the flag only marks the player as hardcore
+            player.hardcore = save.settings.mode == game::GameMode::Hardcore;
+            println!("{}", player);
+            if player.class.is_none() {
+                player.class = Some(read_class());
+            }
+            (player, save.party, save.settings, save.stats)
+        }
+        None => {
+            let mut attacker = entity::Entity::new();
+            let mut settings = game::GameSettings::from_args();
             println!("Welcome to Dungeon Fighter. Let's get your player set up for the game");
+            settings.set_mode(read_game_mode());
+            attacker.hardcore = settings.mode == game::GameMode::Hardcore;
+            attacker.class = Some(read_class());
             println!("{:#?}", attacker);
-            is_first_time = false;
+            (attacker, party::Party::default(), settings, game::RunStats::default())
         }
+    };
+    stats.record_level(attacker.level);
 
+    loop {
         let ans = read_input();
+        stats.turns += 1;
 
         match ans.as_str() {
             "A" | "a" => {
-                defender = entity::Entity::new();
-                defender
-                    .backpack
-                    .add_item(defender.backpack.generate_item());
+                if !attacker.is_alive() {
+                    println!("You are too wounded to fight. Rest at the inn first.");
+                    continue;
+                }
+                let boss_fight = boss::boss_due(attacker.level, stats.bosses_defeated);
+                let mut defenders: Vec<entity::Entity> = if boss_fight {
+                    let boss = boss::next_boss(stats.bosses_defeated);
+                    println!("{} {} blocks your path!", boss.name, boss.title);
+                    vec![entity::Entity::from_boss(&boss, attacker.level)]
+                } else {
+                    (0..template::encounter_size(attacker.level))
+                        .map(|_| {
+                            let mut defender = entity::Entity::new_enemy();
+                            defender
+                                .backpack
+                                .add_item(defender.backpack.generate_item());
+                            defender
+                        })
+                        .collect()
+                };
+                println!("{}", attacker);
+                for defender in &defenders {
+                    println!("{}", defender);
+                }
+                let mut allies = vec![&mut attacker];
+                allies.extend(party.ready());
+                let mut battle = battle::Battle::new(allies, defenders.iter_mut().collect());
+                let result = run_battle(&mut battle);
+                let loot = battle.loot();
+                // The party can finish the fight after the player went down,
+                // but that still counts as the player's defeat
+                let result = match result {
+                    battle::BattleResult::AttackerWon if !attacker.is_alive() => battle::BattleResult::DefenderWon,
+                    result => result,
+                };
+                match result {
+                    battle::BattleResult::AttackerWon => {
+                        stats.battles_won += 1;
+                        stats.xp_earned += loot.xp;
+                        stats.gold_earned += loot.gold;
+                        stats.record_level(attacker.level);
+                        if boss_fight {
+                            stats.bosses_defeated += 1;
+                        } else {
+                            offer_to_spare(&mut party, defenders);
+                        }
+                    }
+                    battle::BattleResult::DefenderWon => {
+                        match game::apply_defeat(&mut attacker, &settings, &mut stats) {
+                            game::DefeatOutcome::Respawned { xp_lost, gold_lost } => {
+                                println!(
+                                    "You were defeated and lost {} xp and {} gold. You wake up at the inn.",
+                                    xp_lost, gold_lost
+                                );
+                                party.restore();
+                                record_run(&attacker, &settings, &stats);
+                            }
+                            game::DefeatOutcome::GameOver => {
+                                if let Err(err) = save::SaveGame::delete() {
+                                    println!("Failed to delete save: {}", err);
+                                }
+                                game::print_game_over(&attacker, &settings, &stats);
+                                record_run(&attacker, &settings, &stats);
+                                return;
+                            }
+                        }
+                    }
+                    battle::BattleResult::Fled => {
+                        stats.battles_fled += 1;
+                    }
+                    battle::BattleResult::DefenderFled | battle::BattleResult::Stalemate => {}
+                }
+                autosave(&attacker, &party, &settings, &stats);
+                // Fighting takes the turn, so no regeneration this time
+                continue;
+            }
+            "R" | "r" => {
+                attacker.restore();
+                party.restore();
+                println!("You rest at the inn and wake up fully healed.");
                 println!("{}", attacker);
-                println!("{}", defender);
-                let mut battle = battle::Battle::new(&mut attacker, &mut defender);
-                battle.attack();
+                autosave(&attacker, &party, &settings, &stats);
+                continue;
             }
-            "L" | "l" => {
-                attacker.level_up();
+            "B" | "b" => {
+                leaderboard::Leaderboard::load(game::GameMode::Normal).print(game::GameMode::Normal);
+                leaderboard::Leaderboard::load(game::GameMode::Hardcore).print(game::GameMode::Hardcore);
+                continue;
             }
             "V" | "v" => {
                 println!("{}", attacker);
+                println!("Mode: {}", settings.mode);
+                println!("Combat Stats: {}", attacker.modifiers);
+                println!("Damage Type: {}, Resistances: {}", attacker.damage_type(), attacker.resistances);
+                if attacker.abilities.is_empty() {
+                    println!("No abilities learned.");
+                } else {
+                    println!("Abilities:");
+                    for ability in &attacker.abilities {
+                        println!("    {}", ability);
+                    }
+                }
                 attacker.backpack.list_items();
+                continue;
             }
             "I" | "i" => {
                 // View items and equip/unequip
                 handle_inventory(&mut attacker);
+                autosave(&attacker, &party, &settings, &stats);
+                continue;
+            }
+            // Waiting is the only thing that lets a turn pass outside of combat
+            "W" | "w" => {}
+            "S" | "s" => {
+                handle_blacksmith(&mut attacker);
+                autosave(&attacker, &party, &settings, &stats);
+                continue;
+            }
+            "C" | "c" => {
+                handle_crafting(&mut attacker);
+                autosave(&attacker, &party, &settings, &stats);
+                continue;
+            }
+            "U" | "u" => {
+                handle_enchanting(&mut attacker);
+                autosave(&attacker, &party, &settings, &stats);
+                continue;
+            }
+            "P" | "p" => {
+                handle_party(&mut attacker, &mut party);
+                autosave(&attacker, &party, &settings, &stats);
+                continue;
+            }
+            "T" | "t" => {
+                handle_skill_tree(&mut attacker);
+                autosave(&attacker, &party, &settings, &stats);
+                continue;
+            }
+            "Q" | "q" => {
+                autosave(&attacker, &party, &settings, &stats);
+                record_run(&attacker, &settings, &stats);
+                break;
             }
-            "Q" | "q" => break,
             _ => {
                 println!("Invalid Input");
+                continue;
+            }
+        }
+
+        // Every turn waited out of combat regenerates a little health and mana
+        let healed = attacker.regenerate();
+        let restored = attacker.regenerate_mana();
+        party.regenerate();
+        if healed > 0 || restored > 0 {
+            println!(
+                "You regenerate {} health ({}/{}) and {} mana ({}/{})",
+                healed, attacker.current_health, attacker.max_health, restored, attacker.current_mana, attacker.max_mana
+            );
+        }
+        autosave(&attacker, &party, &settings, &stats);
+    }
+}
+
+/// Advances the battle one action at a time, asking the player for theirs.
+fn run_battle(battle: &mut battle::Battle) -> battle::BattleResult {
+    println!("{} begins", battle);
+    battle.start();
+    loop {
+        match battle.state() {
+            battle::BattleState::PlayerTurn => {
+                let Some(action) = read_combat_action(battle) else {
+                    continue;
+                };
+                if let Err(err) = battle.player_action(action) {
+                    println!("{}", err);
+                }
+            }
+            battle::BattleState::AiTurn => {
+                battle.ai_turn();
+            }
+            battle::BattleState::Finished(result) => return result,
+        }
+    }
+}
+
+fn read_combat_action(battle: &battle::Battle) -> Option<battle::CombatAction> {
+    println!("Your party:");
+    for entity in battle.allies() {
+        print_combatant(entity);
+    }
+    println!("Enemies:");
+    for entity in battle.enemies() {
+        print_combatant(entity);
+    }
+    println!(
+        "Enter A to Attack\nEnter D to Defend\nEnter U to use an item\nEnter S to use an ability\nEnter F to flee ({}% chance)",
+        battle.flee_chance()
+    );
+    let ans: String = read!();
+
+    match ans.as_str() {
+        "A" | "a" => read_target(battle).map(battle::CombatAction::Attack),
+        "D" | "d" => Some(battle::CombatAction::Defend),
+        "F" | "f" => Some(battle::CombatAction::Flee),
+        "U" | "u" => {
+            let consumables = battle.player().backpack.consumables();
+            if consumables.is_empty() {
+                println!("You have nothing to use.");
+                return None;
+            }
+            for (count, item) in consumables.iter().enumerate() {
+                println!("{}: {} restores {} health", count, item.name, item.heal_amount());
+            }
+            println!("Enter the item number to use: ");
+            let num: String = read!();
+            match num.parse::<usize>() {
+                Ok(num) if num < consumables.len() => {
+                    Some(battle::CombatAction::UseItem(consumables[num].name.clone()))
+                }
+                _ => {
+                    println!("Invalid item number");
+                    None
+                }
+            }
+        }
+        "S" | "s" => {
+            let abilities = &battle.player().abilities;
+            if abilities.is_empty() {
+                println!("You haven't learned any abilities yet.");
+                return None;
             }
+            println!("Mana: {}/{}", battle.player().current_mana, battle.player().max_mana);
+            for (count, ability) in abilities.iter().enumerate() {
+                println!("{}: {}", count, ability);
+            }
+            println!("Enter the ability number to use: ");
+            let num: String = read!();
+            match num.parse::<usize>() {
+                Ok(num) if num < abilities.len() => {
+                    let target = match abilities[num].targeting {
+                        ability::Targeting::Enemy => read_target(battle)?,
+                        ability::Targeting::Caster => 0,
+                    };
+                    Some(battle::CombatAction::UseAbility(num, target))
+                }
+                _ => {
+                    println!("Invalid ability number");
+                    None
+                }
+            }
+        }
+        _ => {
+            println!("Invalid Input");
+            None
+        }
+    }
+}
+
+fn print_combatant(entity: &entity::Entity) {
+    if !entity.is_alive() {
+        println!("    {} (defeated)", entity.name);
+        return;
+    }
+    let effects = entity.status_summary();
+    if effects.is_empty() {
+        println!("    {} ({}/{})", entity.name, entity.current_health, entity.max_health);
+    } else {
+        println!(
+            "    {} ({}/{}) [{}]",
+            entity.name, entity.current_health, entity.max_health, effects
+        );
+    }
+}
+
+/// Picks which enemy to aim at, only asking when there is more than one.
+fn read_target(battle: &battle::Battle) -> Option<usize> {
+    let targets = battle.targets();
+    if targets.len() == 1 {
+        return targets.first().copied();
+    }
+    let enemies = battle.enemies();
+    for index in &targets {
+        println!("{}: {} ({} health)", index, enemies[*index].name, enemies[*index].get_health());
+    }
+    println!("Enter the target number: ");
+    let num: String = read!();
+    match num.parse::<usize>() {
+        Ok(num) if targets.contains(&num) => Some(num),
+        _ => {
+            println!("Invalid target number");
+            None
+        }
+    }
+}
+
+fn record_run(player: &entity::Entity, settings: &game::GameSettings, stats: &game::RunStats) {
+    let mut board = leaderboard::Leaderboard::load(settings.mode);
+    if let Some(rank) = board.record(player, stats) {
+        println!("Your run placed #{} on the {} leaderboard!", rank, settings.mode);
+        if let Err(err) = board.write(settings.mode) {
+            println!("Failed to save the leaderboard: {}", err);
+        }
+    }
+    board.print(settings.mode);
+}
+
+fn read_game_mode() -> game::GameMode {
+    loop {
+        println!("Choose your game mode:\nEnter N for Normal\nEnter H for Hardcore (one life, your save is deleted on death)");
+        let ans: String = read!();
+        match ans.as_str() {
+            "N" | "n" => return game::GameMode::Normal,
+            "H" | "h" => return game::GameMode::Hardcore,
+            _ => println!("Invalid Input"),
+        }
+    }
+}
+
+fn read_class() -> skill_tree::Class {
+    loop {
+        println!("Choose your class:");
+        for (count, class) in skill_tree::Class::ALL.iter().enumerate() {
+            println!("Enter {} for {}", count, class);
+        }
+        let ans: String = read!();
+        match ans.parse::<usize>() {
+            Ok(num) if num < skill_tree::Class::ALL.len() => return skill_tree::Class::ALL[num],
+            _ => println!("Invalid Input"),
+        }
+    }
+}
+
+fn handle_skill_tree(entity: &mut entity::Entity) {
+    let Some(class) = entity.class else {
+        println!("You have no class to train.");
+        return;
+    };
+
+    clear_screen();
+    loop {
+        println!("{} Skill Tree - {} talent points available", class, entity.talent_points);
+        let talents = skill_tree::skill_tree(class);
+        for (count, talent) in talents.iter().enumerate() {
+            let status = if entity.talents.iter().any(|id| id == talent.id) {
+                "[unlocked]".to_string()
+            } else {
+                let missing: Vec<&str> = talents
+                    .iter()
+                    .filter(|other| talent.requires.contains(&other.id) && !entity.talents.iter().any(|id| id == other.id))
+                    .map(|other| other.name)
+                    .collect();
+                if missing.is_empty() {
+                    "[available]".to_string()
+                } else {
+                    format!("[requires {}]", missing.join(", "))
+                }
+            };
+            println!("{}: {} {}", count, talent, status);
+        }
+
+        println!(
+            "Enter U:talent_number to unlock a talent, R to reset your talents for {} gold or B to go back: ",
+            entity.respec_cost()
+        );
+        let ans: String = read!();
+        let parts: Vec<&str> = ans.split(':').collect();
+
+        match parts[0] {
+            "U" | "u" if parts.len() == 2 => match parts[1].parse::<usize>() {
+                Ok(num) if num < talents.len() => match entity.unlock_talent(talents[num].id) {
+                    Ok(()) => println!("Unlocked {}", talents[num].name),
+                    Err(err) => println!("Failed to unlock talent: {}", err),
+                },
+                _ => println!("Invalid talent number"),
+            },
+            "R" | "r" => match entity.respec() {
+                Ok(refunded) => println!("Your talents have been reset and {} points refunded", refunded),
+                Err(err) => println!("Failed to reset talents: {}", err),
+            },
+            "B" | "b" => return,
+            _ => println!("Invalid input format."),
+        }
+    }
+}
+
+fn autosave(player: &entity::Entity, party: &party::Party, settings: &game::GameSettings, stats: &game::RunStats) {
+    let save = save::SaveGame {
+        player: player.clone(),
+        settings: settings.clone(),
+        stats: stats.clone(),
+        party: party.clone(),
+    };
+    if let Err(err) = save.write() {
+        println!("Failed to save the game: {}", err);
+    }
+}
+
+/// Gives one of the fallen enemies the chance to beg for mercy and join the party.
+fn offer_to_spare(party: &mut party::Party, defenders: Vec<entity::Entity>) {
+    if party.is_full() || !party::roll_spare() {
+        return;
+    }
+    let Some(enemy) = defenders.into_iter().find(|defender| !defender.is_alive()) else {
+        return;
+    };
+    println!("{} begs for mercy. Enter Y to spare them and let them join your party: ", enemy.name);
+    let ans: String = read!();
+    if ans.eq_ignore_ascii_case("y") {
+        if let Err(err) = party.spare(enemy) {
+            println!("{}", err);
+        }
+    }
+}
+
+fn handle_party(player: &mut entity::Entity, party: &mut party::Party) {
+    clear_screen();
+    loop {
+        println!("Party - {}/{} companions", party.companions.len(), party::MAX_COMPANIONS);
+        for (count, companion) in party.companions.iter().enumerate() {
+            println!("{}: {}", count, companion);
+        }
+
+        println!("Enter H to hire a companion, G:number to give a companion an item, I:number to manage their inventory, T:number to open their skill tree, N:number to rename, D:number to dismiss or B to go back: ");
+        let ans: String = read!();
+        let parts: Vec<&str> = ans.split(':').collect();
+
+        match parts[0] {
+            "H" | "h" => {
+                let hireling = party::new_hireling(player.level);
+                println!("{}", hireling);
+                println!("Enter Y to hire them for {} gold: ", party::hire_cost(&hireling));
+                let ans: String = read!();
+                if ans.eq_ignore_ascii_case("y") {
+                    if let Err(err) = party.hire(player, hireling) {
+                        println!("Failed to hire: {}", err);
+                    }
+                }
+                continue;
+            }
+            "B" | "b" => return,
+            _ => {}
+        }
+
+        let index = match parts.get(1).map(|num| num.parse::<usize>()) {
+            Some(Ok(num)) if num < party.companions.len() => num,
+            Some(_) => {
+                println!("Invalid companion number");
+                continue;
+            }
+            None => {
+                println!("Invalid input format.");
+                continue;
+            }
+        };
+
+        match parts[0] {
+            "G" | "g" => give_item(player, &mut party.companions[index]),
+            "I" | "i" => handle_inventory(&mut party.companions[index]),
+            "T" | "t" => handle_skill_tree(&mut party.companions[index]),
+            "N" | "n" => {
+                println!("Enter the new name: ");
+                let name: String = read!();
+                if let Err(err) = party.rename(index, &name) {
+                    println!("Failed to rename: {}", err);
+                }
+            }
+            "D" | "d" => match party.dismiss(index) {
+                Ok(mut companion) => {
+                    // Whatever they carried was bought with the party's gold, so it stays
+                    for (_, item) in companion.backpack.equipped_items.drain() {
+                        player.backpack.add_item(item);
+                    }
+                    for item in companion.backpack.remove_items().into_iter().flatten() {
+                        player.backpack.add_item(item);
+                    }
+                    player.gold += companion.gold;
+                    println!("{} leaves your party and hands back their gear and {} gold", companion.name, companion.gold);
+                }
+                Err(err) => println!("Failed to dismiss: {}", err),
+            },
+            _ => println!("Invalid input format."),
+        }
+    }
+}
+
+/// Moves an unequipped item from the player's backpack into a companion's.
+fn give_item(player: &mut entity::Entity, companion: &mut entity::Entity) {
+    let names: Vec<String> = player.backpack.items.keys().cloned().collect();
+    if names.is_empty() {
+        println!("You have nothing to give.");
+        return;
+    }
+    for (count, name) in names.iter().enumerate() {
+        println!("{}: {}", count, name);
+    }
+    println!("Enter the item number to give to {}: ", companion.name);
+    let num: String = read!();
+    match num.parse::<usize>() {
+        Ok(num) if num < names.len() => {
+            if let Some(item) = player.backpack.take_item(&names[num]) {
+                println!("{} now carries {}", companion.name, item.name);
+                companion.backpack.add_item(item);
+            }
+        }
+        _ => println!("Invalid item number"),
+    }
+}
+
+fn handle_blacksmith(entity: &mut entity::Entity) {
+    clear_screen();
+    loop {
+        let damaged: Vec<String> = entity.backpack.damaged_items().iter().map(|item| item.name.clone()).collect();
+        if damaged.is_empty() {
+            println!("The blacksmith finds nothing that needs repairing.");
+            return;
+        }
+
+        println!("Blacksmith - you have {} gold", entity.gold);
+        for (count, item) in entity.backpack.damaged_items().iter().enumerate() {
+            let state = if item.is_broken() { " BROKEN" } else { "" };
+            println!(
+                "{}: {} {}/{}{} - {} gold to repair",
+                count,
+                item.name,
+                item.durability,
+                item.max_durability,
+                state,
+                item.repair_cost()
+            );
+        }
+
+        println!("Enter R:item_number to repair an item, A to repair everything, K:item_number to use a repair kit or B to go back: ");
+        let ans: String = read!();
+        let parts: Vec<&str> = ans.split(':').collect();
+
+        match parts[0] {
+            "A" | "a" => {
+                for item_name in &damaged {
+                    match entity.repair_item(item_name) {
+                        Ok(cost) => println!("Repaired {} for {} gold", item_name, cost),
+                        Err(err) => println!("Failed to repair: {}", err),
+                    }
+                }
+                continue;
+            }
+            "B" | "b" => return,
+            _ => {}
+        }
+
+        let item_name = match parts.get(1).map(|num| num.parse::<usize>()) {
+            Some(Ok(num)) if num < damaged.len() => &damaged[num],
+            Some(_) => {
+                println!("Invalid item number");
+                continue;
+            }
+            None => {
+                println!("Invalid input format.");
+                continue;
+            }
+        };
+
+        match parts[0] {
+            "R" | "r" => match entity.repair_item(item_name) {
+                Ok(cost) => println!("Repaired {} for {} gold", item_name, cost),
+                Err(err) => println!("Failed to repair: {}", err),
+            },
+            "K" | "k" => {
+                let kits: Vec<String> = entity.backpack.repair_kits().iter().map(|kit| kit.name.clone()).collect();
+                if kits.is_empty() {
+                    println!("You have no repair kits.");
+                    continue;
+                }
+                for (count, kit) in entity.backpack.repair_kits().iter().enumerate() {
+                    println!("{}: {}", count, kit);
+                }
+                println!("Enter the repair kit number to use: ");
+                let num: String = read!();
+                match num.parse::<usize>() {
+                    Ok(num) if num < kits.len() => match entity.use_repair_kit(&kits[num], item_name) {
+                        Ok(restored) => println!("{} restores {} durability to {}", kits[num], restored, item_name),
+                        Err(err) => println!("Failed to use repair kit: {}", err),
+                    },
+                    _ => println!("Invalid repair kit number"),
+                }
+            }
+            _ => println!("Invalid input format."),
+        }
+    }
+}
+
+/// The materials an entity carries, for the workbench and enchanter headers.
+fn materials_summary(entity: &entity::Entity) -> String {
+    if entity.backpack.materials.is_empty() {
+        "no materials".to_string()
+    } else {
+        crafting::format_materials(&entity.backpack.material_list())
+    }
+}
+
+fn handle_crafting(entity: &mut entity::Entity) {
+    clear_screen();
+    loop {
+        let recipes = crafting::known_recipes(entity.level);
+        println!("Workbench - you have {} gold and {}", entity.gold, materials_summary(entity));
+        for (count, recipe) in recipes.iter().enumerate() {
+            let missing = recipe.missing(&entity.backpack);
+            if missing.is_empty() && entity.gold >= recipe.gold {
+                println!("{}: {}  [craftable]", count, recipe);
+            } else if missing.is_empty() {
+                println!("{}{}: {}  (needs more gold){}", color::Fg(color::LightBlack), count, recipe, color::Fg(color::Reset));
+            } else {
+                println!(
+                    "{}{}: {}  (missing {}){}",
+                    color::Fg(color::LightBlack),
+                    count,
+                    recipe,
+                    crafting::format_materials(&missing),
+                    color::Fg(color::Reset)
+                );
+            }
+        }
+
+        println!("Enter C:recipe_number to craft or B to go back: ");
+        let ans: String = read!();
+        let parts: Vec<&str> = ans.split(':').collect();
+
+        match parts[0] {
+            "C" | "c" => match parts.get(1).map(|num| num.parse::<usize>()) {
+                Some(Ok(num)) if num < recipes.len() => match entity.craft(&recipes[num]) {
+                    Ok(item_name) => println!("You craft {}", item_name),
+                    Err(err) => println!("Failed to craft: {}", err),
+                },
+                Some(_) => println!("Invalid recipe number"),
+                None => println!("Invalid input format."),
+            },
+            "B" | "b" => return,
+            _ => println!("Invalid input format."),
+        }
+    }
+}
+
+fn handle_enchanting(entity: &mut entity::Entity) {
+    clear_screen();
+    loop {
+        let gear: Vec<String> = entity.backpack.gear().iter().map(|item| item.name.clone()).collect();
+        if gear.is_empty() {
+            println!("You have no gear to upgrade or enchant.");
+            return;
+        }
+
+        println!("Enchanter - you have {} gold and {}", entity.gold, materials_summary(entity));
+        println!(
+            "Enchanting costs {} and {} gold",
+            crafting::format_materials(&enchanting::ENCHANT_MATERIALS),
+            enchanting::ENCHANT_GOLD
+        );
+        for (count, item) in entity.backpack.gear().iter().enumerate() {
+            let enchantment = match &item.enchantment {
+                Some(enchantment) => format!("Enchanted: {}", enchantment),
+                None => "no enchantment".to_string(),
+            };
+            let sockets = format!("{}/{} sockets filled", item.socketed.len(), item.sockets);
+            if item.upgrade_level >= enchanting::MAX_UPGRADE_LEVEL {
+                println!("{}: {} +{}, {}, {} - fully upgraded", count, item.name, item.upgrade_level, enchantment, sockets);
+            } else {
+                let (materials, gold) = enchanting::upgrade_cost(item);
+                println!(
+                    "{}: {} +{}, {}, {} - upgrade for {} and {} gold, {}% chance to fail",
+                    count,
+                    item.name,
+                    item.upgrade_level,
+                    enchantment,
+                    sockets,
+                    crafting::format_materials(&materials),
+                    gold,
+                    enchanting::upgrade_fail_chance(item)
+                );
+            }
+        }
+
+        println!("Enter U:item_number to upgrade an item, E:item_number to enchant an item, S:item_number to socket a gem, X:item_number to remove a gem or B to go back: ");
+        let ans: String = read!();
+        let parts: Vec<&str> = ans.split(':').collect();
+
+        if let "B" | "b" = parts[0] {
+            return;
+        }
+        let item_name = match parts.get(1).map(|num| num.parse::<usize>()) {
+            Some(Ok(num)) if num < gear.len() => &gear[num],
+            Some(_) => {
+                println!("Invalid item number");
+                continue;
+            }
+            None => {
+                println!("Invalid input format.");
+                continue;
+            }
+        };
+
+        match parts[0] {
+            "U" | "u" => match entity.upgrade_item(item_name) {
+                Ok(true) => println!("{} is upgraded!", item_name),
+                Ok(false) => println!("The upgrade fails and the materials are lost."),
+                Err(err) => println!("Failed to upgrade: {}", err),
+            },
+            "E" | "e" => match entity.enchant_item(item_name) {
+                Ok(enchantment) => println!("{} is now enchanted with {}", item_name, enchantment),
+                Err(err) => println!("Failed to enchant: {}", err),
+            },
+            "S" | "s" => {
+                let gems: Vec<String> = entity.backpack.gems().iter().map(|gem| gem.name.clone()).collect();
+                if gems.is_empty() {
+                    println!("You have no gems.");
+                    continue;
+                }
+                let slot = entity.backpack.find_item(item_name).and_then(|item| item.slot);
+                for (count, gem) in entity.backpack.gems().iter().filter_map(|item| item.gem).enumerate() {
+                    println!("{}: {} gives {}", count, gem, gem.bonus(slot));
+                }
+                println!("Enter the gem number to socket: ");
+                let num: String = read!();
+                match num.parse::<usize>() {
+                    Ok(num) if num < gems.len() => match entity.socket_gem(&gems[num], item_name) {
+                        Ok(()) => println!("You set {} into {}", gems[num], item_name),
+                        Err(err) => println!("Failed to socket gem: {}", err),
+                    },
+                    _ => println!("Invalid gem number"),
+                }
+            }
+            "X" | "x" => {
+                let Some(socketed) = entity.backpack.find_item(item_name).map(|item| item.socketed.clone()) else {
+                    continue;
+                };
+                if socketed.is_empty() {
+                    println!("{} has no gems to remove.", item_name);
+                    continue;
+                }
+                for (count, gem) in socketed.iter().enumerate() {
+                    println!("{}: {}", count, gem);
+                }
+                println!("Enter the socket number to empty: ");
+                let num: String = read!();
+                match num.parse::<usize>() {
+                    Ok(num) => match entity.unsocket_gem(item_name, num) {
+                        Ok(gem) => println!("You remove {} from {}", gem, item_name),
+                        Err(err) => println!("Failed to remove gem: {}", err),
+                    },
+                    Err(_) => println!("Invalid socket number"),
+                }
+            }
+            _ => println!("Invalid input format."),
         }
     }
 }
@@ -59,21 +819,39 @@ fn handle_inventory(entity: &mut entity::Entity) {
     clear_screen();
     println!("Inventory Items:");
     for (count, (item_name, item)) in entity.backpack.items.iter().enumerate() {
-        println!(
-            "{}: {} gives +{} damage  Slot: {:?}",
-            count, item_name, item.damage_boost, item.slot
-        );
+        // Items the entity can't equip yet are greyed out with what they still need
+        if item.requirements.is_met(entity) {
+            let state = if item.is_broken() { "  [broken]" } else { "" };
+            println!(
+                "{}: {} gives +{} damage  Slot: {:?}  {}{}",
+                count, item_name, item.total_damage(), item.slot, item.rarity(), state
+            );
+        } else {
+            println!(
+                "{}{}: {} gives +{} damage  Slot: {:?}  {}  (requires {}){}",
+                color::Fg(color::LightBlack),
+                count,
+                item_name,
+                item.total_damage(),
+                item.slot,
+                item.rarity(),
+                item.requirements,
+                color::Fg(color::Reset)
+            );
+        }
     }
 
     println!("Equipped Items:");
     for (count, (item_name, item)) in entity.backpack.equipped_items.iter().enumerate() {
+        let state = if item.is_broken() { "  [broken]" } else { "" };
         println!(
-            "{}: {} gives +{} damage  Slot: {:?}",
-            count, item_name, item.damage_boost, item.slot
+            "{}: {} gives +{} damage  Slot: {:?}{}",
+            count, item_name, item.total_damage(), item.slot, state
         );
     }
+    entity.backpack.print_set_bonuses();
 
-    println!("Enter A:item_number to equip (add) item or R:item_number to unequip item: ");
+    println!("Enter A:item_number to equip (add) item, R:item_number to unequip item, U:item_number to use or read an item, S:item_number to salvage an item or X:rarity to salvage all gear below a rarity (uncommon, rare, set, unique): ");
     let ans: String = read!();
     let parts: Vec<&str> = ans.split(':').collect();
 
@@ -96,17 +874,60 @@ fn handle_inventory(entity: &mut entity::Entity) {
                     if let Err(err) = entity.equip_item(item_name.clone().as_str()) {
                         println!("Failed to equip item: {}", err);
                     } else {
-                        entity.recalculate_damage();
+                        entity.recalculate_stats();
                     }
                 }
                 Err(_) => println!("Invalid item number"),
             }
         }
+        "U" | "u" => {
+            let item_num: Result<usize, _> = parts[1].parse();
+            match item_num {
+                Ok(num) if num < entity.backpack.items.len() => {
+                    let item_name = entity.backpack.items.keys().nth(num).unwrap().clone();
+                    let is_scroll = entity.backpack.items[&item_name].teaches.is_some();
+                    if is_scroll {
+                        match entity.read_scroll(&item_name) {
+                            Ok(ability) => println!("You read {} and learned {}!", item_name, ability),
+                            Err(err) => println!("Failed to read item: {}", err),
+                        }
+                    } else {
+                        match entity.use_item(&item_name) {
+                            Ok(healed) => println!("You use {} and recover {} health", item_name, healed),
+                            Err(err) => println!("Failed to use item: {}", err),
+                        }
+                    }
+                }
+                _ => println!("Invalid item number"),
+            }
+        }
+        "S" | "s" => match parts[1].parse::<usize>() {
+            Ok(num) if num < entity.backpack.items.len() => {
+                let item_name = entity.backpack.items.keys().nth(num).unwrap().clone();
+                match entity.backpack.salvage_item(&item_name) {
+                    Ok(materials) => println!("You salvage {} into {}", item_name, crafting::format_materials(&materials)),
+                    Err(err) => println!("Failed to salvage item: {}", err),
+                }
+            }
+            _ => println!("Invalid item number"),
+        },
+        "X" | "x" => match parse_rarity(parts[1]) {
+            Ok(rarity) => {
+                let salvaged = entity.backpack.salvage_below(rarity);
+                if salvaged.is_empty() {
+                    println!("You have no gear below {} to salvage.", rarity);
+                }
+                for (item_name, materials) in salvaged {
+                    println!("You salvage {} into {}", item_name, crafting::format_materials(&materials));
+                }
+            }
+            Err(_) => println!("Invalid rarity specified."),
+        },
         "R" | "r" => {
             let item_num: Result<usize, _> = parts[1].parse();
             match item_num {
                 Ok(num) => {
-                    if num >= entity.backpack.items.len() {
+                    if num >= entity.backpack.equipped_items.len() {
                         println!("Invalid item number");
                         return;
                     }
@@ -115,7 +936,7 @@ fn handle_inventory(entity: &mut entity::Entity) {
                     if let Ok(slot) = parse_slot(slot_str.to_string().as_str()) {
                         if let Some(item) = entity.unequip_item(&slot) {
                             println!("Unequipped item: {}", item.name);
-                            entity.recalculate_damage();
+                            entity.recalculate_stats();
                         } else {
                             println!("No item found in slot {:?}", slot);
                         }
@@ -133,6 +954,7 @@ fn handle_inventory(entity: &mut entity::Entity) {
 fn parse_slot(slot_str: &str) -> Result<Slot, ()> {
     match slot_str.trim().to_lowercase().as_str() {
         "weapon" => Ok(Slot::Weapon),
+        "offhand" => Ok(Slot::OffHand),
         "helmet" => Ok(Slot::Helmet),
         "chestplate" => Ok(Slot::Chestplate),
         "gauntlets" => Ok(Slot::Gauntlets),
@@ -145,14 +967,26 @@ fn parse_slot(slot_str: &str) -> Result<Slot, ()> {
         "belt" => Ok(Slot::Belt),
         "amulet" => Ok(Slot::Amulet),
         "ring" => Ok(Slot::Ring),
+        "secondring" => Ok(Slot::SecondRing),
         "potion" | "scroll" => Ok(Slot::Potion),
         _ => Err(()),
     }
 }
 
+fn parse_rarity(rarity_str: &str) -> Result<Rarity, ()> {
+    match rarity_str.trim().to_lowercase().as_str() {
+        "common" => Ok(Rarity::Common),
+        "uncommon" => Ok(Rarity::Uncommon),
+        "rare" => Ok(Rarity::Rare),
+        "set" => Ok(Rarity::Set),
+        "unique" => Ok(Rarity::Unique),
+        _ => Err(()),
+    }
+}
+
 // Function to read user input
 fn read_input() -> String {
-    println!("Enter A to Attack\nEnter Q to Quit\nEnter L to level up\nEnter V to view inventory\nEnter I to manage inventory");
+    println!("Enter A to Attack\nEnter Q to Quit\nEnter R to rest at the inn\nEnter W to wait a turn and regenerate\nEnter B to view the leaderboards\nEnter V to view inventory\nEnter I to manage inventory\nEnter T to open the skill tree\nEnter P to manage your party\nEnter S to visit the blacksmith\nEnter C to craft\nEnter U to upgrade, enchant and socket gear");
     let input: String = read!();
     input
 }